@@ -0,0 +1,133 @@
+//! Computer-controlled snake for the --autopilot demo mode
+//!
+//! Each frame a breadth-first search over the grid finds the shortest path
+//! from player 1's head to the nearest food, treating snakes, obstacles,
+//! hazards, tron walls, poison, and portals as blocked. When no food can be reached, any move that
+//! doesn't die on the spot is taken instead.
+
+use crate::game::{Direction, GameState, Point};
+use std::collections::VecDeque;
+
+/// Moves the snake can make: orthogonal ones, plus the diagonals in diagonal mode
+fn directions(game: &GameState) -> &'static [Direction] {
+    if game.settings.diagonal {
+        &Direction::ALL
+    } else {
+        &Direction::ALL[..4]
+    }
+}
+
+/// Direction player 1's snake should take next, or None if every move is fatal
+pub fn choose_direction(game: &GameState) -> Option<Direction> {
+    let snake = game.snake();
+    let head = snake.head();
+
+    // The first step may not reverse onto the snake's own neck
+    let first_steps = directions(game)
+        .iter()
+        .copied()
+        .filter(|direction| !snake.direction.is_opposite(direction));
+
+    path_to_food(game, head, first_steps.clone()).or_else(|| {
+        // No way to the food: at least survive this move
+        first_steps.into_iter().find(|&direction| {
+            step(game, head, direction).is_some_and(|next| !is_blocked(game, &next))
+        })
+    })
+}
+
+/// First direction of the shortest safe path from `head` to the nearest food
+fn path_to_food(
+    game: &GameState,
+    head: Point,
+    first_steps: impl Iterator<Item = Direction>,
+) -> Option<Direction> {
+    let width = game.game_width as usize;
+    let mut visited = vec![false; width * game.game_height as usize];
+    let index = |point: &Point| point.y as usize * width + point.x as usize;
+    visited[index(&head)] = true;
+
+    // Every queued cell remembers which first step led to it
+    let mut queue = VecDeque::new();
+    for direction in first_steps {
+        if let Some(next) = step(game, head, direction) {
+            if !is_blocked(game, &next) && !visited[index(&next)] {
+                visited[index(&next)] = true;
+                queue.push_back((next, direction));
+            }
+        }
+    }
+
+    while let Some((cell, first_direction)) = queue.pop_front() {
+        if game.food.contains(&cell) {
+            return Some(first_direction);
+        }
+
+        for &direction in directions(game) {
+            if let Some(next) = step(game, cell, direction) {
+                if !is_blocked(game, &next) && !visited[index(&next)] {
+                    visited[index(&next)] = true;
+                    queue.push_back((next, first_direction));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Neighbouring cell in `direction`, wrapping or stopping at the walls like the game does
+fn step(game: &GameState, from: Point, direction: Direction) -> Option<Point> {
+    let next = game.step(from, direction);
+    game.in_arena(&next).then_some(next)
+}
+
+/// True if the search must not route through the cell
+fn is_blocked(game: &GameState, cell: &Point) -> bool {
+    game.snakes.iter().any(|snake| snake.contains(cell))
+        || game.obstacles.contains(cell)
+        || game.hazards.contains(cell)
+        || game.tron_walls.contains(cell)
+        || matches!(game.poison, Some((poison, _)) if poison == *cell)
+        || game.is_portal(cell) // Not deadly, but the search doesn't model teleporting
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameSettings;
+
+    fn game_with_snake(segments: &[(i32, i32)]) -> GameState {
+        let mut game = GameState::new(GameSettings::new(10, 5, 100), 0, 0).unwrap();
+        game.snakes[0].set_body(segments.iter().map(|&(x, y)| Point { x, y }));
+        game
+    }
+
+    #[test]
+    fn heads_toward_the_food() {
+        let mut game = game_with_snake(&[(2, 2), (1, 2), (0, 2)]);
+        game.food = vec![Point { x: 2, y: 0 }];
+        assert_eq!(choose_direction(&game), Some(Direction::Up));
+
+        game.food = vec![Point { x: 8, y: 2 }];
+        assert_eq!(choose_direction(&game), Some(Direction::Right));
+    }
+
+    #[test]
+    fn never_reverses_onto_its_neck() {
+        // Food straight behind the snake has to be reached by going around
+        let mut game = game_with_snake(&[(5, 2), (4, 2), (3, 2)]);
+        game.food = vec![Point { x: 1, y: 2 }];
+        let direction = choose_direction(&game).unwrap();
+        assert_ne!(direction, Direction::Left);
+    }
+
+    #[test]
+    fn survives_when_the_food_is_unreachable() {
+        // Snake in the top-right corner heading right; food walled off by obstacles
+        let mut game = game_with_snake(&[(9, 0), (8, 0), (7, 0)]);
+        game.food = vec![Point { x: 0, y: 4 }];
+        game.obstacles = vec![Point { x: 0, y: 3 }, Point { x: 1, y: 4 }, Point { x: 1, y: 3 }];
+        assert_eq!(choose_direction(&game), Some(Direction::Down));
+    }
+}
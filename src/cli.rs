@@ -0,0 +1,457 @@
+//! Command-line argument parsing
+
+use crate::{Palette, Theme};
+use std::path::PathBuf;
+
+/// Printed to stderr when the arguments can't be parsed
+pub const USAGE: &str = "\
+Usage: rust-snake [OPTIONS]
+
+Options:
+  --size <tiny|small|medium|large>  Start right away on this field size, skipping the menus
+  --field <WxH>                     Start right away on a custom field size, e.g. 45x25
+                                    (width 10-200, height 5-100)
+  --map <file>                      Play on a map from a text file (# wall, . open, S start, F food)
+  --speed <ms>                      Movement delay in milliseconds (10-1000)
+  --min-frame <ms>                  Fastest the speed-up and speed keys may go (10-1000, default 40)
+  --max-frame <ms>                  Slowest the speed keys may go (10-1000, default 250)
+  --length <n>                      Segments the snake starts with (1-100, default 3)
+  --max-length <n>                  Snake stops growing at this length; food still scores (2-10000)
+  --fair-spawn                      Food tends to appear far from the snake's head
+  --combos                          Food eaten in quick succession is worth more (10, 15, 20, ...)
+  --chaos                           A powerup that reverses the controls for a while turns up now and then
+  --valued-food                     Every food shows what it's worth (5-9 points) instead of a flat 10
+  --time-attack <seconds>           Score as much as possible before the clock runs out (10-3600)
+  --near-miss                       Passing right alongside your own body earns style points
+  --theme <classic|retro>           Glyphs and colors for the game screen
+  --palette <standard|deuteranopia|protanopia>
+                                    Colors, including ones for red-green color blindness
+  --rainbow                         Snake cycles through the colors of the rainbow
+  --fade                            Snake's body darkens from head to tail
+  --grid                            Faint checkerboard on the playing field
+  --wide                            Draw each cell two columns wide so the field looks square
+  --scroll                          Fields bigger than the terminal scroll to follow the snake
+  --vision                          Tint the cells just ahead of the head
+  --afterimage                      The head leaves a short trail that fades away
+  --ascii                           Plain ASCII walls and snake, even on a UTF-8 terminal
+  --fog <radius>                    Only show the board within this many cells of the head (1-50)
+  --no-wait                         Start moving at once instead of waiting for the first key press
+  --step                            Turn-based: the snake only moves when you press the slow-mo key (Space)
+  --autopilot                       Let the computer play (demo mode)
+  --headless                        No screen: play an --autopilot or --replay game at full speed
+                                    and print the score and number of frames
+  --sound                           Beep when food is eaten and on game over
+  --json                            Print a JSON summary line for each game on exit
+  --seed <n>                        Fixed RNG seed for a reproducible food sequence
+  --fixed-seed                      Restarts keep the game's seed, replaying its food sequence
+                                    (E on the game-over screen switches this)
+  --record <file>                   Save the game's seed and moves to a file
+  --replay <file>                   Play back a recorded game
+  --input-pipe <path>               Also read directions (u/d/l/r bytes) from a named pipe
+  --practice                        Press U after a crash to rewind a few moves and try again
+                                    (practice games don't count for the high score)
+  --debug                           Show the frame number, measured FPS, head and food coordinates,
+                                    and the collision bounds in the top-right corner
+  --title                           Show the live score in the terminal's window/tab title
+  --auto-pause <secs>               Pause after this long without a key press (0-600, 0 = never; default 3)";
+
+/// Options given on the command line
+#[derive(Debug, Default)]
+pub struct Options {
+    pub size: Option<(i32, i32)>,    // Field width and height (--size or --field); skips the menus when set
+    pub map: Option<PathBuf>,        // User-made map file; sets the field size and skips the menus
+    pub speed: Option<u64>,          // Movement delay in milliseconds
+    pub min_frame: Option<u64>,      // Lower bound for speed changes, in milliseconds
+    pub max_frame: Option<u64>,      // Upper bound for speed changes, in milliseconds
+    pub start_length: Option<u32>,   // Segments each snake starts with
+    pub max_length: Option<u32>,     // Length at which the snakes stop growing
+    pub fair_spawn: bool,            // Place food away from the heads
+    pub combos: bool,                // Reward quick consecutive food with rising points
+    pub chaos: bool,                 // Spawn the reverse-controls powerup
+    pub valued_food: bool,           // Food worth 5-9 points, shown as digits
+    pub time_attack: Option<u64>,    // Time limit in seconds
+    pub near_miss: bool,             // Bonus points for brushing past the snake's own body
+    pub theme: Option<&'static Theme>, // Look of the game screen (classic if not given)
+    pub palette: Option<&'static Palette>, // Colors behind the theme (standard if not given)
+    pub rainbow: bool,               // Animate the snake in rainbow colors
+    pub fade: bool,                  // Shade the snake from a bright head to a dark tail
+    pub grid: bool,                  // Draw a dotted checkerboard behind the field
+    pub wide: bool,                  // Two screen columns per cell to even out the aspect ratio
+    pub fog: Option<i32>,            // Fog of war: how far from the heads the board can be seen
+    pub scroll: bool,                // Show a moving slice of fields too big for the terminal
+    pub vision: bool,                // Vision cone in front of the heads
+    pub afterimage: bool,            // Fading trail behind the heads
+    pub ascii: bool,                 // No box-drawing or other Unicode glyphs
+    pub step: bool,                  // Move one cell per key press instead of in real time
+    pub no_wait: bool,               // Skip the press-a-key-to-start wait
+    pub autopilot: bool,             // The computer steers player 1
+    pub headless: bool,              // Skip curses and run the game as fast as possible
+    pub sound: bool,                 // Ring the terminal bell on eating and dying
+    pub json: bool,                  // Print a JSON summary of each game to stdout on exit
+    pub seed: Option<u64>,           // Fixed RNG seed for a reproducible food sequence
+    pub fixed_seed: bool,            // Restart with the same seed instead of a fresh one
+    pub record: Option<PathBuf>,     // Write the game's seed and inputs to this file
+    pub replay: Option<PathBuf>,     // Play back a recording instead of reading the keyboard
+    pub input_pipe: Option<PathBuf>, // FIFO another program writes directions to
+    pub auto_pause: Option<u64>,     // Idle seconds before the game pauses itself (0 = never)
+    pub practice: bool,              // Allow rewinding after a crash; scores aren't kept
+    pub debug: bool,                 // Show frame number and FPS on screen
+    pub title: bool,                 // Keep the score in the terminal title (OSC 0)
+}
+
+/// Parse the arguments following the program name
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size" => {
+                let value = args.next().ok_or("--size requires a value")?;
+                let size = rust_snake::FIELD_SIZES
+                    .iter()
+                    .find(|size| size.name.eq_ignore_ascii_case(&value))
+                    .ok_or_else(|| format!("Invalid size '{}': expected tiny, small, medium, or large", value))?;
+                if options.size.replace((size.width, size.height)).is_some() {
+                    return Err("--size and --field can't be combined".to_string());
+                }
+            }
+            "--field" => {
+                let value = args.next().ok_or("--field requires a value")?;
+                let (width_range, height_range) = (crate::CUSTOM_WIDTH_RANGE, crate::CUSTOM_HEIGHT_RANGE);
+                let size = value
+                    .to_ascii_lowercase()
+                    .split_once('x')
+                    .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+                    .filter(|(width, height)| width_range.contains(width) && height_range.contains(height))
+                    .ok_or_else(|| {
+                        format!(
+                            "Invalid field '{}': expected WIDTHxHEIGHT with a width of {}-{} and a height of {}-{}",
+                            value,
+                            width_range.start(),
+                            width_range.end(),
+                            height_range.start(),
+                            height_range.end()
+                        )
+                    })?;
+                if options.size.replace(size).is_some() {
+                    return Err("--size and --field can't be combined".to_string());
+                }
+            }
+            "--speed" => {
+                let value = args.next().ok_or("--speed requires a value")?;
+                let range = crate::CUSTOM_DELAY_RANGE;
+                let speed = value
+                    .parse()
+                    .ok()
+                    .filter(|delay| range.contains(delay))
+                    .ok_or_else(|| {
+                        format!("Invalid speed '{}': expected {}-{} milliseconds", value, range.start(), range.end())
+                    })?;
+                options.speed = Some(speed);
+            }
+            "--length" => {
+                let value = args.next().ok_or("--length requires a value")?;
+                let range = crate::START_LENGTH_RANGE;
+                let length = value
+                    .parse()
+                    .ok()
+                    .filter(|length| range.contains(length))
+                    .ok_or_else(|| {
+                        format!("Invalid length '{}': expected {}-{} segments", value, range.start(), range.end())
+                    })?;
+                options.start_length = Some(length);
+            }
+            "--min-frame" | "--max-frame" => {
+                let value = args.next().ok_or_else(|| format!("{} requires a value", arg))?;
+                let range = crate::CUSTOM_DELAY_RANGE;
+                let ms = value
+                    .parse()
+                    .ok()
+                    .filter(|ms| range.contains(ms))
+                    .ok_or_else(|| {
+                        format!("Invalid frame bound '{}': expected {}-{} milliseconds", value, range.start(), range.end())
+                    })?;
+                if arg == "--min-frame" {
+                    options.min_frame = Some(ms);
+                } else {
+                    options.max_frame = Some(ms);
+                }
+            }
+            "--max-length" => {
+                let value = args.next().ok_or("--max-length requires a value")?;
+                let range = crate::MAX_LENGTH_RANGE;
+                let length = value
+                    .parse()
+                    .ok()
+                    .filter(|length| range.contains(length))
+                    .ok_or_else(|| {
+                        format!("Invalid max length '{}': expected {}-{} segments", value, range.start(), range.end())
+                    })?;
+                options.max_length = Some(length);
+            }
+            "--fair-spawn" => options.fair_spawn = true,
+            "--combos" => options.combos = true,
+            "--chaos" => options.chaos = true,
+            "--valued-food" => options.valued_food = true,
+            "--near-miss" => options.near_miss = true,
+            "--time-attack" => {
+                let value = args.next().ok_or("--time-attack requires a value")?;
+                let range = crate::TIME_ATTACK_RANGE;
+                let seconds = value
+                    .parse()
+                    .ok()
+                    .filter(|seconds| range.contains(seconds))
+                    .ok_or_else(|| {
+                        format!("Invalid time limit '{}': expected {}-{} seconds", value, range.start(), range.end())
+                    })?;
+                options.time_attack = Some(seconds);
+            }
+            "--theme" => {
+                let value = args.next().ok_or("--theme requires a value")?;
+                let theme = crate::THEMES
+                    .iter()
+                    .find(|theme| theme.name.eq_ignore_ascii_case(&value))
+                    .ok_or_else(|| format!("Invalid theme '{}': expected classic or retro", value))?;
+                options.theme = Some(theme);
+            }
+            "--palette" => {
+                let value = args.next().ok_or("--palette requires a value")?;
+                let palette = crate::PALETTES
+                    .iter()
+                    .find(|palette| palette.name.eq_ignore_ascii_case(&value))
+                    .ok_or_else(|| {
+                        format!("Invalid palette '{}': expected standard, deuteranopia, or protanopia", value)
+                    })?;
+                options.palette = Some(palette);
+            }
+            "--rainbow" => options.rainbow = true,
+            "--fade" => options.fade = true,
+            "--grid" => options.grid = true,
+            "--wide" => options.wide = true,
+            "--autopilot" => options.autopilot = true,
+            "--headless" => options.headless = true,
+            "--sound" => options.sound = true,
+            "--json" => options.json = true,
+            "--seed" => {
+                let value = args.next().ok_or("--seed requires a value")?;
+                let seed = value
+                    .parse()
+                    .map_err(|_| format!("Invalid seed '{}': expected a non-negative integer", value))?;
+                options.seed = Some(seed);
+            }
+            "--fixed-seed" => options.fixed_seed = true,
+            "--record" => {
+                let path = args.next().ok_or("--record requires a file path")?;
+                options.record = Some(PathBuf::from(path));
+            }
+            "--replay" => {
+                let path = args.next().ok_or("--replay requires a file path")?;
+                options.replay = Some(PathBuf::from(path));
+            }
+            "--map" => {
+                let path = args.next().ok_or("--map requires a file path")?;
+                options.map = Some(PathBuf::from(path));
+            }
+            "--input-pipe" => {
+                let path = args.next().ok_or("--input-pipe requires a file path")?;
+                options.input_pipe = Some(PathBuf::from(path));
+            }
+            "--practice" => options.practice = true,
+            "--debug" => options.debug = true,
+            "--fog" => {
+                let value = args.next().ok_or("--fog requires a value")?;
+                let range = crate::FOG_RADIUS_RANGE;
+                let radius = value
+                    .parse()
+                    .ok()
+                    .filter(|radius| range.contains(radius))
+                    .ok_or_else(|| {
+                        format!("Invalid fog radius '{}': expected {}-{} cells", value, range.start(), range.end())
+                    })?;
+                options.fog = Some(radius);
+            }
+            "--title" => options.title = true,
+            "--scroll" => options.scroll = true,
+            "--vision" => options.vision = true,
+            "--afterimage" => options.afterimage = true,
+            "--ascii" => options.ascii = true,
+            "--step" => options.step = true,
+            "--no-wait" => options.no_wait = true,
+            "--auto-pause" => {
+                let value = args.next().ok_or("--auto-pause requires a value")?;
+                let range = crate::AUTO_PAUSE_RANGE;
+                let secs = value
+                    .parse()
+                    .ok()
+                    .filter(|secs| range.contains(secs))
+                    .ok_or_else(|| {
+                        format!("Invalid auto-pause '{}': expected {}-{} seconds", value, range.start(), range.end())
+                    })?;
+                options.auto_pause = Some(secs);
+            }
+            _ => return Err(format!("Unknown argument '{}'", arg)),
+        }
+    }
+
+    let replay_conflict = options.autopilot
+        || options.input_pipe.is_some()
+        || options.record.is_some()
+        || options.seed.is_some()
+        || options.size.is_some()
+        || options.map.is_some()
+        || options.speed.is_some()
+        || options.min_frame.is_some()
+        || options.max_frame.is_some()
+        || options.start_length.is_some()
+        || options.max_length.is_some()
+        || options.fair_spawn
+        || options.combos
+        || options.chaos
+        || options.valued_food
+        || options.time_attack.is_some()
+        || options.near_miss
+        || options.practice;
+    if options.replay.is_some() && replay_conflict {
+        return Err("--replay can't be combined with --autopilot, --input-pipe, --record, --seed, --size, --field, \
+            --map, --speed, --min-frame, --max-frame, --length, --max-length, --fair-spawn, --combos, --chaos, --valued-food, --time-attack, --near-miss, or --practice"
+            .to_string());
+    }
+
+    let min_frame = options.min_frame.unwrap_or(rust_snake::game::DEFAULT_MIN_FRAME_MS);
+    let max_frame = options.max_frame.unwrap_or(rust_snake::game::DEFAULT_MAX_FRAME_MS);
+    if min_frame > max_frame {
+        return Err(format!("--min-frame ({} ms) can't be above --max-frame ({} ms)", min_frame, max_frame));
+    }
+
+    if options.rainbow && options.fade {
+        return Err("--rainbow and --fade can't be combined".to_string());
+    }
+
+    // Replays and headless games play themselves; there's nobody to press the step key
+    if options.step && (options.replay.is_some() || options.headless) {
+        return Err("--step can't be combined with --replay or --headless".to_string());
+    }
+
+    // The map decides the field size, and recordings don't carry it
+    if options.map.is_some() && (options.size.is_some() || options.record.is_some()) {
+        return Err("--map can't be combined with --size, --field, or --record".to_string());
+    }
+
+    if options.headless && !options.autopilot && options.replay.is_none() {
+        return Err("--headless needs --autopilot or --replay".to_string());
+    }
+    if options.headless && (options.record.is_some() || options.input_pipe.is_some()) {
+        return Err("--headless can't be combined with --record or --input-pipe".to_string());
+    }
+
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_seed() {
+        assert_eq!(parse(&["--seed", "1234"]).unwrap().seed, Some(1234));
+        assert_eq!(parse(&[]).unwrap().seed, None);
+        assert!(parse(&["--fixed-seed"]).unwrap().fixed_seed);
+    }
+
+    #[test]
+    fn parses_record_and_replay_paths() {
+        let options = parse(&["--record", "run.txt"]).unwrap();
+        assert_eq!(options.record, Some(PathBuf::from("run.txt")));
+
+        let options = parse(&["--replay", "run.txt"]).unwrap();
+        assert_eq!(options.replay, Some(PathBuf::from("run.txt")));
+
+        let options = parse(&["--map", "maze.txt"]).unwrap();
+        assert_eq!(options.map, Some(PathBuf::from("maze.txt")));
+        let options = parse(&["--input-pipe", "/tmp/snake"]).unwrap();
+        assert_eq!(options.input_pipe, Some(PathBuf::from("/tmp/snake")));
+        assert!(parse(&["--input-pipe", "a", "--replay", "b"]).is_err());
+    }
+
+    #[test]
+    fn parses_size_and_speed() {
+        let options = parse(&["--size", "Small", "--speed", "80"]).unwrap();
+        assert_eq!(options.size, Some((30, 20)));
+        assert_eq!(options.speed, Some(80));
+        assert_eq!(parse(&["--length", "1"]).unwrap().start_length, Some(1));
+        assert_eq!(parse(&["--max-length", "12"]).unwrap().max_length, Some(12));
+        let options = parse(&["--min-frame", "60", "--max-frame", "150"]).unwrap();
+        assert_eq!((options.min_frame, options.max_frame), (Some(60), Some(150)));
+        assert_eq!(parse(&["--field", "45x25"]).unwrap().size, Some((45, 25)));
+        assert_eq!(parse(&["--field", "200X5"]).unwrap().size, Some((200, 5)));
+        assert!(!options.sound);
+        assert!(parse(&["--sound"]).unwrap().sound);
+        assert!(parse(&["--autopilot"]).unwrap().autopilot);
+        assert!(parse(&["--json"]).unwrap().json);
+        assert!(parse(&["--rainbow"]).unwrap().rainbow);
+        assert!(parse(&["--fade"]).unwrap().fade);
+        assert!(parse(&["--grid"]).unwrap().grid);
+        assert!(parse(&["--practice"]).unwrap().practice);
+        assert!(parse(&["--fair-spawn"]).unwrap().fair_spawn);
+        assert!(parse(&["--combos"]).unwrap().combos);
+        assert!(parse(&["--chaos"]).unwrap().chaos);
+        assert!(parse(&["--valued-food"]).unwrap().valued_food);
+        assert!(parse(&["--near-miss"]).unwrap().near_miss);
+        assert_eq!(parse(&["--time-attack", "90"]).unwrap().time_attack, Some(90));
+        assert!(parse(&["--debug"]).unwrap().debug);
+        assert!(parse(&["--title"]).unwrap().title);
+        assert!(parse(&["--scroll"]).unwrap().scroll);
+        assert!(parse(&["--vision"]).unwrap().vision);
+        assert!(parse(&["--afterimage"]).unwrap().afterimage);
+        assert!(parse(&["--ascii"]).unwrap().ascii);
+        assert!(parse(&["--step"]).unwrap().step);
+        assert!(parse(&["--no-wait"]).unwrap().no_wait);
+        assert_eq!(parse(&["--fog", "4"]).unwrap().fog, Some(4));
+        assert!(parse(&["--wide"]).unwrap().wide);
+        assert!(parse(&["--headless", "--autopilot"]).unwrap().headless);
+        assert_eq!(parse(&["--theme", "retro"]).unwrap().theme.map(|theme| theme.name), Some("retro"));
+        let palette = parse(&["--palette", "Protanopia"]).unwrap().palette;
+        assert_eq!(palette.map(|palette| palette.name), Some("protanopia"));
+        assert_eq!(parse(&["--auto-pause", "0"]).unwrap().auto_pause, Some(0));
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(parse(&["--size", "huge"]).is_err());
+        assert!(parse(&["--theme", "neon"]).is_err());
+        assert!(parse(&["--palette", "sepia"]).is_err());
+        assert!(parse(&["--speed", "5"]).is_err());
+        assert!(parse(&["--speed", "fast"]).is_err());
+        assert!(parse(&["--length", "0"]).is_err());
+        assert!(parse(&["--max-length", "1"]).is_err());
+        assert!(parse(&["--min-frame", "5"]).is_err());
+        assert!(parse(&["--min-frame", "300"]).is_err());
+        assert!(parse(&["--min-frame", "80", "--max-frame", "60"]).is_err());
+        assert!(parse(&["--fog", "0"]).is_err());
+        assert!(parse(&["--time-attack", "5"]).is_err());
+        assert!(parse(&["--field", "45"]).is_err());
+        assert!(parse(&["--field", "9x25"]).is_err());
+        assert!(parse(&["--field", "45x-3"]).is_err());
+        assert!(parse(&["--field", "30x20", "--size", "tiny"]).is_err());
+        assert!(parse(&["--map", "a", "--size", "tiny"]).is_err());
+        assert!(parse(&["--map", "a", "--record", "b"]).is_err());
+        assert!(parse(&["--replay", "a", "--length", "5"]).is_err());
+        assert!(parse(&["--replay", "a", "--size", "tiny"]).is_err());
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--replay", "a", "--record", "b"]).is_err());
+        assert!(parse(&["--replay", "a", "--practice"]).is_err());
+        assert!(parse(&["--replay", "a", "--step"]).is_err());
+        assert!(parse(&["--seed", "abc"]).is_err());
+        assert!(parse(&["--auto-pause", "-1"]).is_err());
+        assert!(parse(&["--auto-pause", "601"]).is_err());
+        assert!(parse(&["--headless"]).is_err());
+        assert!(parse(&["--headless", "--autopilot", "--record", "a"]).is_err());
+        assert!(parse(&["--rainbow", "--fade"]).is_err());
+        assert!(parse(&["--bogus"]).is_err());
+    }
+}
@@ -0,0 +1,233 @@
+//! User configuration loaded from `~/.rust-snake.conf`
+//!
+//! The file holds `action = key` lines, for example:
+//!
+//! ```text
+//! # Vim-style movement
+//! up = k
+//! down = j
+//! left = h
+//! right = l
+//! ```
+//!
+//! Recognized actions: up, down, left, right, pause, quit, restart, scores, slowmo,
+//! faster, slower, help, menu, rewind, seed, reroll.
+//! Arrow keys always move the snake regardless of the configured keys.
+
+use rust_snake::Direction;
+use std::fs;
+use std::path::PathBuf;
+
+const FILE_NAME: &str = ".rust-snake.conf";
+
+/// Path of a file in the home directory (or the current directory as fallback)
+pub fn home_path(file_name: &str) -> PathBuf {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE")) // Windows
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(file_name)
+}
+
+/// Something the player can trigger from the keyboard during a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Move(Direction),
+    Pause,
+    Quit,
+    Restart,
+    Scores, // Show the leaderboard (after a game ends)
+    SlowMo, // Toggle slow motion
+    Faster, // Shorten the movement delay
+    Slower, // Lengthen the movement delay
+    Help,   // Open or close the help overlay
+    Menu,   // Back to the menus for a new game (after game over)
+    Rewind, // Take back the last few moves (after game over, in practice mode)
+    Seed,   // Switch between restarting with the same seed or a new one (after game over)
+    Reroll, // Move the food somewhere else for a few points (a few times per game)
+}
+
+/// Character keys bound to each action (letters match case-insensitively)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBindings {
+    pub up: char,
+    pub down: char,
+    pub left: char,
+    pub right: char,
+    pub pause: char,
+    pub quit: char,
+    pub restart: char,
+    pub scores: char,
+    pub slowmo: char,
+    pub faster: char,
+    pub slower: char,
+    pub help: char,
+    pub menu: char,
+    pub rewind: char,
+    pub seed: char,
+    pub reroll: char,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            up: 'w',
+            down: 's',
+            left: 'a',
+            right: 'd',
+            pause: 'p',
+            quit: 'q',
+            restart: 'r',
+            scores: 'l',
+            slowmo: ' ',
+            faster: '+',
+            slower: '-',
+            help: 'h',
+            menu: 'm',
+            rewind: 'u',
+            seed: 'e',
+            reroll: 'f',
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Action bound to a typed character, if any
+    pub fn action_for(&self, c: char) -> Option<Action> {
+        let bindings = [
+            (self.up, Action::Move(Direction::Up)),
+            (self.down, Action::Move(Direction::Down)),
+            (self.left, Action::Move(Direction::Left)),
+            (self.right, Action::Move(Direction::Right)),
+            (self.pause, Action::Pause),
+            (self.quit, Action::Quit),
+            (self.restart, Action::Restart),
+            (self.scores, Action::Scores),
+            (self.slowmo, Action::SlowMo),
+            (self.faster, Action::Faster),
+            (self.slower, Action::Slower),
+            (self.help, Action::Help),
+            (self.menu, Action::Menu),
+            (self.rewind, Action::Rewind),
+            (self.seed, Action::Seed),
+            (self.reroll, Action::Reroll),
+        ];
+        bindings
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(&c))
+            .map(|&(_, action)| action)
+    }
+
+    /// Key as shown in on-screen hints
+    pub fn label(key: char) -> String {
+        match key {
+            ' ' => "Space".to_string(),
+            _ => key.to_ascii_uppercase().to_string(),
+        }
+    }
+}
+
+/// Settings read from the config file
+#[derive(Debug, Default)]
+pub struct Config {
+    pub keys: KeyBindings,
+}
+
+/// Load the config file; a missing file gives the defaults
+/// Returns the config plus warnings for any entries that were ignored
+pub fn load() -> (Config, Vec<String>) {
+    match fs::read_to_string(home_path(FILE_NAME)) {
+        Ok(contents) => parse(&contents),
+        Err(_) => (Config::default(), Vec::new()),
+    }
+}
+
+/// Parse config file contents, skipping (and reporting) invalid lines
+pub fn parse(contents: &str) -> (Config, Vec<String>) {
+    let mut config = Config::default();
+    let mut warnings = Vec::new();
+
+    for (line_number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let warn = |message: String| format!("{} line {}: {}", FILE_NAME, line_number + 1, message);
+
+        let Some((name, value)) = line.split_once('=') else {
+            warnings.push(warn(format!("expected 'action = key', got '{}'", line)));
+            continue;
+        };
+        let (name, value) = (name.trim(), value.trim());
+
+        let mut chars = value.chars();
+        let key = match (chars.next(), chars.next()) {
+            (Some(key), None) if !key.is_whitespace() => key,
+            _ => {
+                warnings.push(warn(format!("'{}' must be a single character, got '{}'", name, value)));
+                continue;
+            }
+        };
+
+        let keys = &mut config.keys;
+        let slot = match name {
+            "up" => &mut keys.up,
+            "down" => &mut keys.down,
+            "left" => &mut keys.left,
+            "right" => &mut keys.right,
+            "pause" => &mut keys.pause,
+            "quit" => &mut keys.quit,
+            "restart" => &mut keys.restart,
+            "scores" => &mut keys.scores,
+            "slowmo" => &mut keys.slowmo,
+            "faster" => &mut keys.faster,
+            "slower" => &mut keys.slower,
+            "help" => &mut keys.help,
+            "menu" => &mut keys.menu,
+            "rewind" => &mut keys.rewind,
+            "seed" => &mut keys.seed,
+            "reroll" => &mut keys.reroll,
+            _ => {
+                warnings.push(warn(format!("unknown setting '{}'", name)));
+                continue;
+            }
+        };
+        *slot = key;
+    }
+
+    (config, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_key_bindings() {
+        let (config, warnings) = parse("# vim keys\nup = k\ndown=j\n\nquit = x\n");
+
+        assert!(warnings.is_empty());
+        assert_eq!(config.keys.up, 'k');
+        assert_eq!(config.keys.down, 'j');
+        assert_eq!(config.keys.left, 'a'); // Untouched default
+        assert_eq!(config.keys.action_for('K'), Some(Action::Move(Direction::Up)));
+        assert_eq!(config.keys.action_for('x'), Some(Action::Quit));
+        assert_eq!(config.keys.action_for('q'), None);
+        assert_eq!(config.keys.action_for(' '), Some(Action::SlowMo));
+        assert_eq!(KeyBindings::label(config.keys.slowmo), "Space");
+        assert_eq!(config.keys.action_for('+'), Some(Action::Faster));
+        assert_eq!(config.keys.action_for('U'), Some(Action::Rewind));
+        assert_eq!(config.keys.action_for('e'), Some(Action::Seed));
+        assert_eq!(config.keys.action_for('F'), Some(Action::Reroll));
+    }
+
+    #[test]
+    fn invalid_entries_are_ignored_with_warnings() {
+        let (config, warnings) = parse("jump = j\nup = kk\nnonsense\nleft = h\n");
+
+        assert_eq!(warnings.len(), 3);
+        assert_eq!(config.keys.up, 'w');
+        assert_eq!(config.keys.left, 'h');
+    }
+}
@@ -0,0 +1,3102 @@
+//! Core game logic: snake movement, collisions, food and scoring.
+//! Has no curses dependency so it can be unit tested and driven by any frontend.
+
+use crate::levels::LEVELS;
+use crate::map::Map;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+// ============================================================================
+// GAME CONSTANTS
+// ============================================================================
+
+/// A named board size offered by frontends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldSize {
+    pub name: &'static str,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Preset board sizes, smallest first
+pub const FIELD_SIZES: [FieldSize; 4] = [
+    FieldSize {
+        name: "Tiny",
+        width: 20,
+        height: 10,
+    },
+    FieldSize {
+        name: "Small",
+        width: 30,
+        height: 20,
+    },
+    FieldSize {
+        name: "Medium",
+        width: 40,
+        height: 30,
+    },
+    FieldSize {
+        name: "Large",
+        width: 60,
+        height: 40,
+    },
+];
+
+// Progressive speed-up: every SPEED_UP_SCORE_INTERVAL points the frame
+// duration shrinks by SPEED_UP_STEP_MS, but never below the settings' min_frame_ms
+const SPEED_UP_SCORE_INTERVAL: u32 = 50;
+const SPEED_UP_STEP_MS: u64 = 5;
+
+// Default bounds for every speed change (speed-up and speed keys), in milliseconds per move
+pub const DEFAULT_MIN_FRAME_MS: u64 = 40;
+pub const DEFAULT_MAX_FRAME_MS: u64 = 250;
+
+// Length-based speed-up: the frame duration falls in step with the snake's growth,
+// reaching min_frame_ms once it fills this share of the board
+const LENGTH_CURVE_FULL_PERCENT: i32 = 25;
+
+// Manual speed changes during play: each key press moves the delay by this much,
+// staying within the settings' min_frame_ms and max_frame_ms
+const SPEED_ADJUST_STEP_MS: i64 = 10;
+
+// Bonus food: sometimes appears after regular food is eaten, worth extra
+// points but only for a limited number of frames
+const BONUS_POINTS: u32 = 50;
+const BONUS_SPAWN_CHANCE: f64 = 0.2;
+const BONUS_LIFETIME_FRAMES: u32 = 50;
+
+// Poison food: may appear after regular food is eaten (at the settings' chance),
+// away from the snakes' heads; eating it cuts off tail segments and costs points
+const POISON_SHRINK: usize = 3;
+const POISON_PENALTY: u32 = 20;
+const POISON_LIFETIME_FRAMES: u32 = 80;
+const POISON_MIN_HEAD_DISTANCE: i32 = 3;
+const POISON_PLACEMENT_ATTEMPTS: u32 = 20;
+
+// Reverse-controls powerup (chaos mode): may appear after regular food is eaten; eating it
+// turns every direction key around for REVERSE_EFFECT_FRAMES moves
+const REVERSE_SPAWN_CHANCE: f64 = 0.15;
+const REVERSE_LIFETIME_FRAMES: u32 = 60;
+const REVERSE_EFFECT_FRAMES: u64 = 40;
+
+// Fair spawn: regular food goes on whichever of this many random free cells is farthest from the heads
+const FAIR_SPAWN_CANDIDATES: usize = 5;
+
+// Once fewer than this percentage of the arena's cells are open, a random free cell is picked
+// from a list of them rather than by guessing until one is free, which takes long on a full board
+const SCARCE_CELLS_PERCENT: usize = 10;
+
+// Turns a snake keeps buffered ahead of its moves (next_direction included); quick key
+// sequences each get their own move, and keys past this are dropped
+const TURN_QUEUE_LENGTH: usize = 3;
+
+// Moves the heads and the score stay highlighted after food is eaten
+const EAT_FLASH_FRAMES: u32 = 2;
+
+// Earlier head cells remembered per snake, for a frontend's fading afterimage
+pub const HEAD_TRAIL_LENGTH: usize = 4;
+
+// Combos: food eaten within COMBO_WINDOW_FRAMES moves of the last one is worth
+// COMBO_STEP_POINTS more than the one before, up to COMBO_MAX_LEVEL steps above the base 10
+const COMBO_WINDOW_FRAMES: u32 = 30;
+const COMBO_STEP_POINTS: u32 = 5;
+const COMBO_MAX_LEVEL: u32 = 4;
+
+// Near misses: after each move, every segment of a snake's own body (past its neck) lying
+// right next to the head earns this many points
+const NEAR_MISS_POINTS: u32 = 2;
+
+// Rerolls: times per game the food can be moved somewhere else, and the points each one costs
+pub const REROLLS_PER_GAME: u32 = 3;
+pub const REROLL_COST: u32 = 5;
+
+// Mode name for the plain rules; records saved before they were kept per mode count as this
+pub const CLASSIC_MODE: &str = "classic";
+
+// Valued food: each food is worth a random number of points in this range instead of 10
+pub const FOOD_VALUE_RANGE: std::ops::RangeInclusive<u32> = 5..=9;
+
+// Hunger: a snake warns it's running out of time once this fraction of the limit is left
+const HUNGER_WARNING_DIVISOR: u32 = 4;
+
+// Slow motion: while active each move takes twice as long, using up one frame of budget per move;
+// while inactive the budget recharges by one frame every SLOWMO_RECHARGE_MOVES moves
+pub const SLOWMO_MAX_BUDGET: u32 = 40;
+const SLOWMO_RECHARGE_MOVES: u32 = 4;
+
+// Bigger boards keep more food out at once: one item per this many cells (at least one)
+const CELLS_PER_FOOD: i32 = 400;
+
+// Smallest board that holds the three-segment starting snake (two of them side by side in versus)
+const MIN_BOARD_WIDTH: i32 = 5;
+const MIN_BOARD_HEIGHT: i32 = 3;
+const MIN_VERSUS_WIDTH: i32 = 8;
+
+// Countdown after the first key press: "3... 2... 1... GO!", one step per second
+const COUNTDOWN_START: u8 = 3;
+const COUNTDOWN_STEP: Duration = Duration::from_secs(1);
+
+// Shrinking arena: the walls move in by one cell on every side this often
+// (in moves at the starting delay, so speed changes don't shift it), until
+// the arena would get smaller than the minimum
+const SHRINK_INTERVAL: Duration = Duration::from_secs(15);
+const MIN_ARENA_WIDTH: i32 = 6;
+const MIN_ARENA_HEIGHT: i32 = 4;
+
+// Portals need room around them; smaller fields play without
+const PORTAL_MIN_WIDTH: i32 = 10;
+const PORTAL_MIN_HEIGHT: i32 = 6;
+const PORTAL_PLACEMENT_ATTEMPTS: u32 = 200;
+
+// ============================================================================
+// GAME STATE STRUCTURES
+// ============================================================================
+
+/// A board cell; (0, 0) is the top-left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Heading of a snake
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    // Diagonal mode only
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Point {
+    /// Direction of an adjacent cell, treating cells across opposite edges
+    /// of a width x height board as adjacent (wrap mode)
+    pub fn direction_to(&self, other: &Point, width: i32, height: i32) -> Option<Direction> {
+        // Single step along one axis, possibly across the edge
+        let step = |delta: i32, size: i32| match delta {
+            -1..=1 => Some(delta),
+            _ if delta == size - 1 => Some(-1),
+            _ if delta == -(size - 1) => Some(1),
+            _ => None,
+        };
+        let offset = (step(other.x - self.x, width)?, step(other.y - self.y, height)?);
+        Direction::ALL.into_iter().find(|direction| direction.offset() == offset)
+    }
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+        Direction::UpLeft,
+        Direction::UpRight,
+        Direction::DownLeft,
+        Direction::DownRight,
+    ];
+
+    /// Grid step (dx, dy) taken when moving in this direction
+    pub fn offset(&self) -> (i32, i32) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::UpLeft => (-1, -1),
+            Direction::UpRight => (1, -1),
+            Direction::DownLeft => (-1, 1),
+            Direction::DownRight => (1, 1),
+        }
+    }
+
+    /// True for the four diagonal directions (diagonal mode only)
+    pub fn is_diagonal(&self) -> bool {
+        let (dx, dy) = self.offset();
+        dx != 0 && dy != 0
+    }
+
+    /// Returns true if the other direction is opposite to this one
+    pub fn is_opposite(&self, other: &Direction) -> bool {
+        let (dx, dy) = self.offset();
+        other.offset() == (-dx, -dy)
+    }
+
+    /// The direction pointing the other way (up for down, down-left for up-right, ...)
+    pub fn opposite(&self) -> Direction {
+        *Direction::ALL.iter().find(|direction| self.is_opposite(direction)).unwrap()
+    }
+}
+
+/// Options picked in the menus; kept on GameState so a restart reuses them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameSettings {
+    pub width: i32,
+    pub height: i32,
+    pub delay_ms: u64,
+    pub min_frame_ms: u64, // Fastest the speed-up and speed keys may go (a faster delay_ms stays as is)
+    pub max_frame_ms: u64, // Slowest the speed keys may go (a slower delay_ms stays as is)
+    pub wrap_walls: bool, // Leaving the field re-enters on the opposite edge instead of dying
+    pub doorways: bool, // Solid walls with a gap mid-way along each side that wraps to the opposite gap
+    pub layout: Layout,   // Obstacle arrangement inside the field
+    pub self_collision_forgiveness: u32, // Self-collisions forgiven per game (0 = classic rules)
+    pub versus: bool, // Two players share the board; the last snake alive wins
+    pub hazard_trail: bool, // Every eaten food leaves a deadly hazard where it was
+    pub portals: bool, // Place a linked pair of teleport tiles on the board
+    pub diagonal: bool, // Eight-direction movement (diagonal input is ignored otherwise)
+    pub shrinking: bool, // The walls close in over time
+    pub growth_per_food: u32, // Segments added for each food eaten (1 = classic)
+    pub poison_chance: u32, // Percent chance of poison food after each regular food (0 = never)
+    pub tron: bool, // Every cell the tail leaves becomes a permanent wall
+    pub hunger_limit: u32, // Moves a snake may go without food before it loses a segment (0 = never)
+    pub start_length: u32, // Segments each snake starts with (cut short where the board or a wall ends)
+    pub max_length: u32, // Longest a snake grows; past it food only scores (0 = no limit)
+    pub fair_spawn: bool, // Food appears on the farthest of a few random cells from the heads
+    pub allow_pause: bool, // False for hardcore runs: no pausing, not even the auto-pause
+    pub speed_curve: SpeedCurve, // What the automatic speed-up follows
+    pub combos: bool, // Food eaten in quick succession scores more and more (flat 10 otherwise)
+    pub chaos: bool, // A powerup that reverses the controls for a while turns up now and then
+    pub valued_food: bool, // Every food shows its own value (FOOD_VALUE_RANGE) instead of being worth 10
+    pub time_limit: Option<Duration>, // Time attack: the game ends when this much play time has passed
+    pub near_miss: bool, // Passing right alongside the snake's own body earns NEAR_MISS_POINTS per segment
+    pub assist: bool, // The frontend marks the cell ahead when the next move would be deadly
+}
+
+impl GameSettings {
+    /// Classic rules (solid walls, no obstacles) on a board of the given size
+    pub fn new(width: i32, height: i32, delay_ms: u64) -> Self {
+        GameSettings {
+            width,
+            height,
+            delay_ms,
+            min_frame_ms: DEFAULT_MIN_FRAME_MS,
+            max_frame_ms: DEFAULT_MAX_FRAME_MS,
+            wrap_walls: false,
+            doorways: false,
+            layout: Layout::Open,
+            self_collision_forgiveness: 0,
+            versus: false,
+            hazard_trail: false,
+            portals: false,
+            diagonal: false,
+            shrinking: false,
+            growth_per_food: 1,
+            poison_chance: 0,
+            tron: false,
+            hunger_limit: 0,
+            start_length: 3,
+            max_length: 0,
+            fair_spawn: false,
+            allow_pause: true,
+            speed_curve: SpeedCurve::Score,
+            combos: false,
+            chaos: false,
+            valued_food: false,
+            time_limit: None,
+            near_miss: false,
+            assist: false,
+        }
+    }
+
+    /// Name the high score and leaderboard are kept under: the options that change what
+    /// a score is worth, joined with '+' in a fixed order (CLASSIC_MODE when none are on)
+    /// Speed, field size, and the cosmetic or helping options don't split the records
+    pub fn mode_name(&self) -> String {
+        let mut tags: Vec<String> = Vec::new();
+        if self.wrap_walls {
+            tags.push("wrap".to_string());
+        } else if self.doorways {
+            tags.push("doorways".to_string());
+        }
+        match self.layout {
+            Layout::Open => {}
+            Layout::Blocks => tags.push("blocks".to_string()),
+            Layout::Campaign => tags.push("campaign".to_string()),
+        }
+        let flags = [
+            (self.shrinking, "shrinking"),
+            (self.hazard_trail, "trail"),
+            (self.portals, "portals"),
+            (self.diagonal, "diagonal"),
+            (self.tron, "tron"),
+            (self.poison_chance > 0, "poison"),
+            (self.hunger_limit > 0, "hunger"),
+            (self.self_collision_forgiveness > 0, "forgiving"),
+            (self.combos, "combos"),
+            (self.valued_food, "valued"),
+            (self.chaos, "chaos"),
+            (self.near_miss, "nearmiss"),
+        ];
+        tags.extend(flags.iter().filter(|(on, _)| *on).map(|(_, tag)| tag.to_string()));
+        if self.growth_per_food > 1 {
+            tags.push(format!("growth{}", self.growth_per_food));
+        }
+        if self.max_length > 0 {
+            tags.push(format!("max{}", self.max_length));
+        }
+        if let Some(limit) = self.time_limit {
+            tags.push(format!("time{}s", limit.as_secs()));
+        }
+
+        if tags.is_empty() {
+            CLASSIC_MODE.to_string()
+        } else {
+            tags.join("+")
+        }
+    }
+}
+
+/// What drives the automatic speed-up during a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpeedCurve {
+    Score,  // A little faster every SPEED_UP_SCORE_INTERVAL points
+    Length, // From the starting delay down to the floor as the longest snake fills the board
+}
+
+/// Static obstacle arrangements the player can choose from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    Open,   // No obstacles
+    Blocks, // Four 2x2 blocks, one in each quarter of the field
+    Campaign, // The walls of `levels::LEVELS`, moving on to the next level at each goal score
+}
+
+impl Layout {
+    /// Obstacle cells for a board of the given size
+    fn obstacles(self, width: i32, height: i32) -> Vec<Point> {
+        match self {
+            Layout::Open => Vec::new(),
+            Layout::Campaign => LEVELS[0].obstacles(width, height),
+            Layout::Blocks => {
+                let xs = [width / 4, (width * 3) / 4 - 1];
+                let ys = [height / 4, (height * 3) / 4 - 1];
+
+                let mut obstacles = Vec::new();
+                for &x in &xs {
+                    for &y in &ys {
+                        // 2x2 block with its top-left corner at (x, y)
+                        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                            obstacles.push(Point { x: x + dx, y: y + dy });
+                        }
+                    }
+                }
+                obstacles
+            }
+        }
+    }
+}
+
+/// Whether the game is running, held, or over
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameStatus {
+    Playing,
+    Paused,
+    Dying { frames_left: u32 }, // Death animation playing; turns into GameOver when it runs out
+    GameOver,
+    Won, // Snake filled the entire board
+}
+
+/// One player's snake
+#[derive(Clone)]
+pub struct Snake {
+    body: VecDeque<Point>, // Head first
+    cells: HashSet<Point>, // The same cells as `body`, for constant-time lookups
+    pub direction: Direction,
+    pub next_direction: Direction, // Buffered direction to prevent double-key issues
+    queued_turns: VecDeque<Direction>, // Turns pressed after next_direction, taken one per move
+    pub alive: bool,
+    pub hunger: u32, // Moves left before going hungry costs a segment (hunger mode only)
+    pending_growth: u32, // Moves left on which the tail stays put
+}
+
+impl Snake {
+    /// Snake of `length` segments with its head at `head`, trailing away from `direction`
+    fn new(head: Point, direction: Direction, length: usize) -> Self {
+        let (dx, dy) = direction.offset();
+        let body: VecDeque<Point> = (0..length as i32)
+            .map(|i| Point {
+                x: head.x - dx * i,
+                y: head.y - dy * i,
+            })
+            .collect();
+
+        Snake {
+            cells: body.iter().copied().collect(),
+            body,
+            direction,
+            next_direction: direction,
+            queued_turns: VecDeque::new(),
+            alive: true,
+            hunger: 0,
+            pending_growth: 0,
+        }
+    }
+
+    /// Cell of the snake's head
+    pub fn head(&self) -> Point {
+        *self.body.front().unwrap()
+    }
+
+    /// Segments from head to tail
+    pub fn body(&self) -> &VecDeque<Point> {
+        &self.body
+    }
+
+    /// True if any segment, head included, covers the cell
+    pub fn contains(&self, point: &Point) -> bool {
+        self.cells.contains(point)
+    }
+
+    /// Replace the whole body (head first), e.g. to set up a position in tests
+    #[cfg(test)]
+    pub(crate) fn set_body(&mut self, body: impl IntoIterator<Item = Point>) {
+        self.body = body.into_iter().collect();
+        self.cells = self.body.iter().copied().collect();
+    }
+
+    /// Add a new head segment
+    /// The cell must not be part of the body: drop the tail first when the head follows it
+    fn push_front(&mut self, point: Point) {
+        self.body.push_front(point);
+        self.cells.insert(point);
+    }
+
+    /// Remove the tail segment, returning the cell it covered
+    fn pop_back(&mut self) -> Option<Point> {
+        let tail = self.body.pop_back()?;
+        self.cells.remove(&tail);
+        Some(tail)
+    }
+
+    /// True if the cell will still be covered once the snake moves: the tail moves
+    /// off its cell unless the snake is growing
+    fn occupies_after_move(&self, point: &Point) -> bool {
+        let tail_leaves = self.pending_growth == 0 && self.body.back() == Some(point);
+        self.contains(point) && !tail_leaves
+    }
+
+    /// Buffer a turn if it's valid (not opposite to the turn before it, nor the same again)
+    /// The first turn becomes next_direction; later ones queue up behind it
+    fn set_direction(&mut self, new_direction: Direction) {
+        if self.next_direction == self.direction {
+            if !self.direction.is_opposite(&new_direction) {
+                self.next_direction = new_direction;
+            }
+            return;
+        }
+        let last = self.queued_turns.back().copied().unwrap_or(self.next_direction);
+        let valid = new_direction != last && !last.is_opposite(&new_direction);
+        if valid && self.queued_turns.len() + 1 < TURN_QUEUE_LENGTH {
+            self.queued_turns.push_back(new_direction);
+        }
+    }
+
+    /// Head the way of the buffered turn, and line up the next queued one
+    fn take_turn(&mut self) {
+        self.direction = self.next_direction;
+        if let Some(turn) = self.queued_turns.pop_front() {
+            self.next_direction = turn;
+        }
+    }
+}
+
+/// Everything about one game in progress
+///
+/// A frontend calls `update` once per `step_duration`, feeds in directions with
+/// `set_player_direction` (after `start_countdown`), and draws the public fields
+/// (snakes, food, obstacles, status, score) however it likes.
+#[derive(Clone)]
+pub struct GameState {
+    pub snakes: Vec<Snake>, // Player 1 first; a second snake in versus mode
+    pub head_trails: Vec<VecDeque<Point>>, // Each snake's last HEAD_TRAIL_LENGTH head cells, newest first
+    pub winner: Option<usize>, // Versus mode: index of the surviving snake (None = draw)
+    pub food: Vec<Point>, // Regular food items; topped up to food_target() whenever one is eaten
+    pub food_values: HashMap<Point, u32>, // Valued food: points for each food cell (others are worth 10)
+    pub bonus: Option<(Point, u32)>, // Bonus food position and frames until it disappears
+    pub poison: Option<(Point, u32)>, // Poison food position and frames until it disappears
+    pub reverser: Option<(Point, u32)>, // Chaos mode: reverse-controls powerup and frames until it disappears
+    pub controls_inverted_until_frame: u64, // The frontend reverses direction keys until this frame
+    pub obstacles: Vec<Point>, // Static walls inside the field
+    pub hazards: Vec<Point>, // Trail mode: spots where food was eaten, deadly like walls
+    pub tron_walls: HashSet<Point>, // Tron mode: cells the snakes' tails have left, deadly like walls
+    pub portals: Option<(Point, Point)>, // Linked teleport tiles; entering one exits past the other
+    pub level: usize, // Campaign layout: index of the current level in LEVELS
+    map: Option<Map>, // User-made map replacing the layout and starting position (set up again on restart)
+    pub score: u32,
+    pub frame: u64, // Number of moves made so far (paused/waiting frames don't count)
+    pub ate_food: bool, // Some snake ate food during the last update
+    pub eat_flash_frames: u32, // Moves left to highlight the heads and score after eating
+    pub style: u32, // Near misses: body segments the heads have brushed past so far
+    pub rerolls_left: u32, // Times the food can still be moved with reroll_food this game
+    pub combo_level: u32, // Combos: steps the next quick food scores above the base 10 (0 = no combo)
+    pub frames_since_last_food: u32, // Moves since food was last eaten (counts from the start at first)
+    pub self_collision_forgiveness: u32, // Self-collisions left that cancel the move instead of ending the game
+    pub status: GameStatus,
+    pub game_width: i32,
+    pub game_height: i32,
+    pub inset: i32, // Shrinking arena: how far the walls have moved in on every side
+    shrink_elapsed: Duration, // Moves since the walls last moved, timed at the starting delay
+    pub offset_x: i32, // Offset for centering the game window
+    pub offset_y: i32, // Offset for centering the game window
+    pub waiting_for_start: bool, // Initial pause until first arrow key press
+    pub countdown: Option<u8>, // Seconds left before the first move (Some(0) shows "GO!")
+    countdown_elapsed: Duration, // Time spent on the current countdown step
+    countdown_tick: Instant, // Up to when the countdown has counted real time (pauses don't count)
+    pub terminal_too_small: bool, // Field no longer fits after a resize (game is held paused)
+    pub frames_since_input: u32, // Moves made since the last key press
+    pub auto_pause_frames: u32, // Pause after this many moves without input (0 = never)
+    pub auto_paused: bool, // The current pause was triggered by the auto-pause
+    pub death_frames: u32, // Updates the death animation lasts before game over (0 = none)
+    pub rewind_limit: usize, // Moves kept for rewinding (practice mode; 0 = no rewinding)
+    pub fixed_seed: bool, // Restarts reuse this game's seed (same food sequence) instead of a fresh one
+    no_wait: bool, // Skip the wait for a first key press, on restarts too
+    history: VecDeque<GameState>, // Snapshots taken before each of the last moves, oldest first
+    pub confirm_quit: bool, // Quit was pressed mid-game; held paused until the player answers
+    pub show_help: bool, // The help overlay is open; held paused until it's closed
+    pub frame_duration: Duration, // Current movement delay, shrinks as the score grows
+    speed_adjust_ms: i64, // Manual change to the delay from adjust_speed (negative = faster)
+    pub slowmo: bool, // Slow motion is switched on (doubles the time per move)
+    pub slowmo_budget: u32, // Slow-motion moves left before it switches itself off
+    slowmo_recharge: u32, // Moves made toward the next recharged frame of budget
+    pub start_time: Instant, // When the snake started moving (after the countdown)
+    paused_time: Duration, // Time spent paused since start_time
+    pause_started: Option<Instant>, // Set while paused
+    ended_at: Option<Instant>, // When the game was lost or won
+    pub time_up_frame: Option<u64>, // Time attack without real time (replays, headless runs): the move the clock runs out at
+    timed_out: bool, // The time attack clock ran out
+    pub settings: GameSettings,
+    pub high_score: u32,      // Best score known so far (persisted between runs)
+    pub new_high_score: bool, // This game beat the previous record
+    pub seed: u64, // Seed the food RNG was created from (for reproducing a game)
+    rng: StdRng,
+}
+
+impl GameState {
+    /// Create a game whose food sequence is seeded from entropy
+    /// Fails if the board is too small to hold the starting snake(s)
+    pub fn new(settings: GameSettings, offset_x: i32, offset_y: i32) -> Result<Self, String> {
+        Self::new_seeded(settings, offset_x, offset_y, rand::random())
+    }
+
+    /// Create a game whose food sequence is fully determined by `seed`
+    /// Fails if the board is too small to hold the starting snake(s)
+    pub fn new_seeded(
+        settings: GameSettings,
+        offset_x: i32,
+        offset_y: i32,
+        seed: u64,
+    ) -> Result<Self, String> {
+        let min_width = if settings.versus { MIN_VERSUS_WIDTH } else { MIN_BOARD_WIDTH };
+        if settings.width < min_width || settings.height < MIN_BOARD_HEIGHT {
+            return Err(format!(
+                "A {}x{} board is too small: {} needs at least {}x{}",
+                settings.width,
+                settings.height,
+                if settings.versus { "a two-player game" } else { "the starting snake" },
+                min_width,
+                MIN_BOARD_HEIGHT
+            ));
+        }
+        Ok(Self::build(settings, offset_x, offset_y, seed))
+    }
+
+    /// Set up a game on a board already known to be big enough
+    fn build(settings: GameSettings, offset_x: i32, offset_y: i32, seed: u64) -> Self {
+        let obstacles = settings.layout.obstacles(settings.width, settings.height);
+        let snakes = starting_snakes(&settings, &obstacles);
+        let mut game = GameState {
+            head_trails: vec![VecDeque::new(); snakes.len()],
+            snakes,
+            winner: None,
+            food: Vec::new(), // Filled by spawn_food
+            food_values: HashMap::new(),
+            bonus: None,
+            poison: None,
+            reverser: None,
+            controls_inverted_until_frame: 0,
+            obstacles,
+            hazards: Vec::new(),
+            tron_walls: HashSet::new(),
+            portals: None,
+            level: 0,
+            map: None,
+            score: 0,
+            frame: 0,
+            ate_food: false,
+            eat_flash_frames: 0,
+            style: 0,
+            rerolls_left: REROLLS_PER_GAME,
+            combo_level: 0,
+            frames_since_last_food: COMBO_WINDOW_FRAMES, // The first food can't continue a combo
+            self_collision_forgiveness: settings.self_collision_forgiveness,
+            status: GameStatus::Playing,
+            game_width: settings.width,
+            game_height: settings.height,
+            inset: 0,
+            shrink_elapsed: Duration::ZERO,
+            offset_x,
+            offset_y,
+            waiting_for_start: true, // Start paused until first arrow key
+            countdown: None,
+            countdown_elapsed: Duration::ZERO,
+            countdown_tick: Instant::now(),
+            terminal_too_small: false,
+            frames_since_input: 0,
+            auto_pause_frames: 0, // Off unless the caller turns it on
+            auto_paused: false,
+            death_frames: 0, // Off unless the caller turns it on
+            rewind_limit: 0,
+            fixed_seed: false,
+            no_wait: false,
+            history: VecDeque::new(),
+            confirm_quit: false,
+            show_help: false,
+            frame_duration: Duration::from_millis(settings.delay_ms),
+            speed_adjust_ms: 0,
+            slowmo: false,
+            slowmo_budget: SLOWMO_MAX_BUDGET,
+            slowmo_recharge: 0,
+            start_time: Instant::now(),
+            paused_time: Duration::ZERO,
+            pause_started: None,
+            ended_at: None,
+            time_up_frame: None,
+            timed_out: false,
+            settings,
+            high_score: 0, // Loaded from disk by the caller
+            new_high_score: false,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        };
+
+        game.spawn_food();
+        if settings.portals {
+            game.place_portals();
+        }
+        game
+    }
+
+    /// Put the two portals somewhere clear of the snakes, food, obstacles, and each other
+    /// Leaves them out if the field is too small or crowded to find such spots
+    fn place_portals(&mut self) {
+        if self.game_width < PORTAL_MIN_WIDTH || self.game_height < PORTAL_MIN_HEIGHT {
+            return;
+        }
+
+        let mut placed: Vec<Point> = Vec::with_capacity(2);
+        for _ in 0..PORTAL_PLACEMENT_ATTEMPTS {
+            // Stay off the edges so every cell next to a portal is on the board
+            let cell = Point {
+                x: self.rng.gen_range(1..self.game_width - 1),
+                y: self.rng.gen_range(1..self.game_height - 1),
+            };
+
+            // Keep a one-cell margin so nothing sits right where the snake comes out
+            let near = |other: &Point| (other.x - cell.x).abs() <= 1 && (other.y - cell.y).abs() <= 1;
+            let crowded = self.snakes.iter().any(|snake| snake.body.iter().any(near))
+                || self.obstacles.iter().any(near)
+                || placed.iter().any(near)
+                || self.food.iter().any(near);
+            if !crowded {
+                placed.push(cell);
+                if placed.len() == 2 {
+                    self.portals = Some((placed[0], placed[1]));
+                    return;
+                }
+            }
+        }
+    }
+
+    /// True if the cell holds one of the portals
+    pub fn is_portal(&self, point: &Point) -> bool {
+        matches!(self.portals, Some((a, b)) if a == *point || b == *point)
+    }
+
+    /// Width and height of the playable area inside the (possibly shrunk) walls
+    pub fn arena_size(&self) -> (i32, i32) {
+        (self.game_width - 2 * self.inset, self.game_height - 2 * self.inset)
+    }
+
+    /// True if the cell lies inside the walls
+    pub fn in_arena(&self, point: &Point) -> bool {
+        let (width, height) = self.arena_size();
+        (self.inset..self.inset + width).contains(&point.x) && (self.inset..self.inset + height).contains(&point.y)
+    }
+
+    /// Assist: the cell a player's next move goes into, if that move would kill the snake
+    /// Mirrors the collision checks in `update` without moving anything (a head-on meeting
+    /// with the other snake's next head isn't predicted, and a forgiven bite isn't deadly)
+    pub fn danger_ahead(&self, player: usize) -> Option<Point> {
+        let snake = self.snakes.get(player).filter(|snake| snake.alive)?;
+        let direction = snake.next_direction;
+        let mut new_head = self.step(snake.head(), direction);
+        if let Some((a, b)) = self.portals {
+            if new_head == a {
+                new_head = self.step(b, direction);
+            } else if new_head == b {
+                new_head = self.step(a, direction);
+            }
+        }
+
+        let hits_self = if self.settings.tron {
+            snake.contains(&new_head)
+        } else {
+            snake.occupies_after_move(&new_head)
+        };
+        let deadly = !self.in_arena(&new_head)
+            || (hits_self && self.self_collision_forgiveness == 0)
+            || self.obstacles.contains(&new_head)
+            || self.hazards.contains(&new_head)
+            || self.tron_walls.contains(&new_head)
+            || self.snakes.iter().enumerate().any(|(j, other)| j != player && other.contains(&new_head));
+        deadly.then_some(new_head)
+    }
+
+    /// The cell one step from `from`, wrapped around the arena's edges in wrap mode
+    /// (or through a doorway in doorway mode)
+    pub fn step(&self, from: Point, direction: Direction) -> Point {
+        let (dx, dy) = direction.offset();
+        let mut next = Point {
+            x: from.x + dx,
+            y: from.y + dy,
+        };
+
+        // Wrap mode: re-enter on the opposite edge instead of hitting the wall; doorways
+        // only let the snake through in the doorway row (left/right) or column (top/bottom)
+        let (width, height) = self.arena_size();
+        let (wrap_x, wrap_y) = match self.doorway_lines() {
+            _ if self.settings.wrap_walls => (true, true),
+            Some((door_x, door_y)) => (next.y == door_y, next.x == door_x),
+            None => (false, false),
+        };
+        if wrap_x {
+            next.x = self.inset + (next.x - self.inset).rem_euclid(width);
+        }
+        if wrap_y {
+            next.y = self.inset + (next.y - self.inset).rem_euclid(height);
+        }
+        next
+    }
+
+    /// Doorway mode: the column of the gaps in the top and bottom walls and the row of the
+    /// gaps in the side walls, half-way along the (possibly shrunk) arena
+    fn doorway_lines(&self) -> Option<(i32, i32)> {
+        if !self.settings.doorways {
+            return None;
+        }
+        let (width, height) = self.arena_size();
+        Some((self.inset + width / 2, self.inset + height / 2))
+    }
+
+    /// Wall cells that are doorways: top, bottom, left, and right (empty outside doorway mode)
+    pub fn doorways(&self) -> Vec<Point> {
+        let Some((door_x, door_y)) = self.doorway_lines() else {
+            return Vec::new();
+        };
+        let (width, height) = self.arena_size();
+        vec![
+            Point { x: door_x, y: self.inset - 1 },
+            Point { x: door_x, y: self.inset + height },
+            Point { x: self.inset - 1, y: door_y },
+            Point { x: self.inset + width, y: door_y },
+        ]
+    }
+
+    /// Player 1's snake (the only one outside versus mode)
+    pub fn snake(&self) -> &Snake {
+        &self.snakes[0]
+    }
+
+    /// True if any snake's body covers the cell
+    fn snake_at(&self, point: &Point) -> bool {
+        self.snakes.iter().any(|snake| snake.contains(point))
+    }
+
+    /// Number of arena cells not taken by snakes, obstacles, hazards, tron walls, or portals
+    fn free_cell_count(&self) -> usize {
+        let (width, height) = self.arena_size();
+        let snake_cells = self
+            .snakes
+            .iter()
+            .flat_map(|snake| snake.body.iter())
+            .filter(|segment| self.in_arena(segment))
+            .count();
+        let obstacle_cells = self.obstacles.iter().filter(|obstacle| self.in_arena(obstacle)).count();
+        // A fresh hazard sits under the snake that ate there until its tail moves on
+        let open_hazards = self
+            .hazards
+            .iter()
+            .filter(|hazard| self.in_arena(hazard) && !self.snake_at(hazard))
+            .count();
+        let tron_cells = self.tron_walls.iter().filter(|wall| self.in_arena(wall)).count();
+        let portal_cells = if self.portals.is_some() { 2 } else { 0 };
+        // A cell counted twice (an obstacle under a snake, say) mustn't take the count below zero
+        ((width * height) as usize)
+            .saturating_sub(snake_cells + obstacle_cells + open_hazards + tron_cells + portal_cells)
+    }
+
+    /// Pick a random arena cell not covered by a snake, obstacles, hazards, tron walls, portals, or any food
+    /// Returns None if there is none; callers should check open_cell_count first, as guessing
+    /// goes on until it finds one while the count says plenty are left
+    fn random_free_cell(&mut self) -> Option<Point> {
+        let (width, height) = self.arena_size();
+
+        // Nearly full: guessing would mostly miss, so choose among the open cells themselves
+        if self.open_cell_count() * 100 < (width * height) as usize * SCARCE_CELLS_PERCENT {
+            let open_cells: Vec<Point> = (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| Point {
+                    x: self.inset + x,
+                    y: self.inset + y,
+                })
+                .filter(|cell| self.is_open_cell(cell))
+                .collect();
+            if open_cells.is_empty() {
+                return None;
+            }
+            return Some(open_cells[self.rng.gen_range(0..open_cells.len())]);
+        }
+
+        loop {
+            let cell = Point {
+                x: self.inset + self.rng.gen_range(0..width),
+                y: self.inset + self.rng.gen_range(0..height),
+            };
+            if self.is_open_cell(&cell) {
+                return Some(cell);
+            }
+        }
+    }
+
+    /// Check that a cell isn't taken by a snake, an obstacle, a hazard, a portal, or any food
+    fn is_open_cell(&self, cell: &Point) -> bool {
+        let on_bonus = matches!(self.bonus, Some((bonus, _)) if bonus == *cell);
+        let on_poison = matches!(self.poison, Some((poison, _)) if poison == *cell);
+        let on_reverser = matches!(self.reverser, Some((reverser, _)) if reverser == *cell);
+        !self.snake_at(cell)
+            && !self.obstacles.contains(cell)
+            && !self.hazards.contains(cell)
+            && !self.tron_walls.contains(cell)
+            && !self.is_portal(cell)
+            && !self.food.contains(cell)
+            && !on_bonus
+            && !on_poison
+            && !on_reverser
+    }
+
+    /// Number of regular food items kept on the board, scaled by its area
+    pub fn food_target(&self) -> usize {
+        (self.game_width * self.game_height / CELLS_PER_FOOD).max(1) as usize
+    }
+
+    /// Free cells not already holding food, the bonus, poison, or the reverse powerup
+    fn open_cell_count(&self) -> usize {
+        let extra_cells = usize::from(self.bonus.is_some())
+            + usize::from(self.poison.is_some())
+            + usize::from(self.reverser.is_some());
+        self.free_cell_count().saturating_sub(self.food.len() + extra_cells)
+    }
+
+    /// Top the food up to the target count on cells clear of snakes, obstacles, and hazards
+    fn spawn_food(&mut self) {
+        while self.food.len() < self.food_target() {
+            if self.open_cell_count() == 0 {
+                // Regular food takes priority if the bonus, poison, or powerup holds the last free cell
+                if self.food.is_empty() && self.reverser.is_some() {
+                    self.reverser = None;
+                } else if self.food.is_empty() && self.poison.is_some() {
+                    self.poison = None;
+                } else if self.food.is_empty() && self.bonus.is_some() {
+                    self.bonus = None;
+                } else {
+                    break;
+                }
+            }
+            let cell = if self.settings.fair_spawn {
+                self.fair_free_cell()
+            } else {
+                self.random_free_cell()
+            };
+            let Some(cell) = cell else { break };
+            self.place_food(cell);
+        }
+    }
+
+    /// Put regular food on a cell, giving it a value of its own in valued food mode
+    fn place_food(&mut self, cell: Point) {
+        self.food.push(cell);
+        if self.settings.valued_food {
+            let value = self.rng.gen_range(FOOD_VALUE_RANGE);
+            self.food_values.insert(cell, value);
+        }
+    }
+
+    /// Free cell picked from a few random candidates as the one farthest (in steps along
+    /// the grid) from the nearest living head; None if there is no free cell
+    fn fair_free_cell(&mut self) -> Option<Point> {
+        let heads: Vec<Point> = self.snakes.iter().filter(|snake| snake.alive).map(|snake| snake.head()).collect();
+        let distance = |cell: &Point| {
+            heads
+                .iter()
+                .map(|head| (head.x - cell.x).abs() + (head.y - cell.y).abs())
+                .min()
+                .unwrap_or(0)
+        };
+        let mut best = self.random_free_cell()?;
+        for _ in 1..FAIR_SPAWN_CANDIDATES {
+            let cell = self.random_free_cell()?;
+            if distance(&cell) > distance(&best) {
+                best = cell;
+            }
+        }
+        Some(best)
+    }
+
+    /// Maybe place a short-lived bonus food (needs a cell besides the regular food)
+    fn maybe_spawn_bonus(&mut self) {
+        if self.bonus.is_none() && self.open_cell_count() > 0 && self.rng.gen_bool(BONUS_SPAWN_CHANCE) {
+            self.bonus = self.random_free_cell().map(|cell| (cell, BONUS_LIFETIME_FRAMES));
+        }
+    }
+
+    /// Maybe place short-lived poison food, at the settings' chance, a few cells away from every head
+    /// Gives up quietly if no such cell turns up
+    fn maybe_spawn_poison(&mut self) {
+        let chance = f64::from(self.settings.poison_chance.min(100)) / 100.0;
+        if self.poison.is_some() || self.open_cell_count() == 0 || !self.rng.gen_bool(chance) {
+            return;
+        }
+        for _ in 0..POISON_PLACEMENT_ATTEMPTS {
+            let Some(cell) = self.random_free_cell() else { return };
+            let near_head = self.snakes.iter().any(|snake| {
+                let head = snake.head();
+                (head.x - cell.x).abs().max((head.y - cell.y).abs()) < POISON_MIN_HEAD_DISTANCE
+            });
+            if !near_head {
+                self.poison = Some((cell, POISON_LIFETIME_FRAMES));
+                return;
+            }
+        }
+    }
+
+    /// Chaos mode: maybe place the short-lived reverse-controls powerup
+    fn maybe_spawn_reverser(&mut self) {
+        if self.settings.chaos
+            && self.reverser.is_none()
+            && self.open_cell_count() > 0
+            && self.rng.gen_bool(REVERSE_SPAWN_CHANCE)
+        {
+            self.reverser = self.random_free_cell().map(|cell| (cell, REVERSE_LIFETIME_FRAMES));
+        }
+    }
+
+    /// Whether the frontend should turn direction keys around (chaos mode's powerup is in effect)
+    /// The snake still can't reverse into itself: the turned-around direction is checked as usual
+    pub fn controls_inverted(&self) -> bool {
+        self.frame < self.controls_inverted_until_frame
+    }
+
+    /// Steer the given player's snake (ignored if opposite to its current direction,
+    /// or diagonal outside diagonal mode)
+    pub fn set_player_direction(&mut self, player: usize, new_direction: Direction) {
+        if new_direction.is_diagonal() && !self.settings.diagonal {
+            return;
+        }
+        if let Some(snake) = self.snakes.get_mut(player) {
+            snake.set_direction(new_direction);
+        }
+    }
+
+    /// Leave the waiting-for-start state and begin the countdown to the first move
+    pub fn start_countdown(&mut self) {
+        if self.waiting_for_start {
+            self.waiting_for_start = false;
+            self.countdown = Some(COUNTDOWN_START);
+            self.countdown_elapsed = Duration::ZERO;
+            self.countdown_tick = Instant::now();
+        }
+    }
+
+    /// True if the next update moves the snakes
+    pub fn will_move(&self) -> bool {
+        self.status == GameStatus::Playing && !self.waiting_for_start && self.countdown.is_none()
+    }
+
+    /// Switch slow motion on or off (it only turns on with budget left)
+    pub fn toggle_slowmo(&mut self) {
+        self.slowmo = !self.slowmo && self.slowmo_budget > 0;
+    }
+
+    /// Real time between moves: the frame duration, doubled in slow motion
+    pub fn step_duration(&self) -> Duration {
+        if self.slowmo {
+            self.frame_duration * 2
+        } else {
+            self.frame_duration
+        }
+    }
+
+    /// Main game logic update - called once per frame
+    pub fn update(&mut self) {
+        self.ate_food = false;
+
+        // The death animation plays out on its own; nothing moves meanwhile
+        if let GameStatus::Dying { frames_left } = self.status {
+            let status = match frames_left {
+                0 | 1 => GameStatus::GameOver,
+                _ => GameStatus::Dying { frames_left: frames_left - 1 },
+            };
+            self.set_status(status);
+            return;
+        }
+
+        if self.status != GameStatus::Playing {
+            return;
+        }
+
+        // Don't move snake until player presses first arrow key
+        if self.waiting_for_start {
+            return;
+        }
+
+        // Hold still while counting down, in real time so the speed keys, speed-up, and slow
+        // motion can't stretch it
+        if let Some(remaining) = self.countdown {
+            let now = Instant::now();
+            self.countdown_elapsed += now.saturating_duration_since(self.countdown_tick);
+            self.countdown_tick = now;
+            if self.countdown_elapsed >= COUNTDOWN_STEP {
+                self.countdown_elapsed -= COUNTDOWN_STEP;
+                self.countdown = remaining.checked_sub(1);
+                if self.countdown.is_none() && self.frame == 0 {
+                    // The clock starts with the first move (later countdowns don't reset it)
+                    self.start_time = Instant::now();
+                    self.paused_time = Duration::ZERO;
+                }
+            }
+            return;
+        }
+
+        // Nobody has touched the keyboard for a while: pause before the snake crashes
+        self.frames_since_input += 1;
+        if self.settings.allow_pause && self.auto_pause_frames > 0 && self.frames_since_input > self.auto_pause_frames {
+            self.frames_since_input = 0;
+            self.set_status(GameStatus::Paused);
+            self.auto_paused = true;
+            return;
+        }
+
+        self.save_snapshot();
+        self.frame += 1;
+        self.eat_flash_frames = self.eat_flash_frames.saturating_sub(1);
+
+        // A combo runs out once the next food took too long
+        self.frames_since_last_food = self.frames_since_last_food.saturating_add(1);
+        if self.frames_since_last_food > COMBO_WINDOW_FRAMES {
+            self.combo_level = 0;
+        }
+
+        // Slow motion drains its budget while on and slowly recharges while off
+        if self.slowmo {
+            self.slowmo_budget -= 1;
+            self.slowmo = self.slowmo_budget > 0;
+        } else {
+            self.slowmo_recharge += 1;
+            if self.slowmo_recharge == SLOWMO_RECHARGE_MOVES {
+                self.slowmo_recharge = 0;
+                self.slowmo_budget = (self.slowmo_budget + 1).min(SLOWMO_MAX_BUDGET);
+            }
+        }
+
+        // Bonus and poison food (and the powerup) only stick around for a limited number of frames
+        for item in [&mut self.bonus, &mut self.poison, &mut self.reverser] {
+            if let Some((_, frames_left)) = item {
+                *frames_left -= 1;
+                if *frames_left == 0 {
+                    *item = None;
+                }
+            }
+        }
+
+        // Work out where every living snake is heading; all snakes move at once
+        let mut new_heads: Vec<Option<Point>> = Vec::with_capacity(self.snakes.len());
+        for i in 0..self.snakes.len() {
+            if !self.snakes[i].alive {
+                new_heads.push(None);
+                continue;
+            }
+
+            // Update direction (prevents 180-degree turns within one frame)
+            let previous_direction = self.snakes[i].direction;
+            self.snakes[i].take_turn();
+
+            // Calculate new head position
+            let direction = self.snakes[i].direction;
+            let mut new_head = self.step(self.snakes[i].head(), direction);
+
+            // Entering a portal comes out just past its partner, still heading the same way
+            if let Some((a, b)) = self.portals {
+                if new_head == a {
+                    new_head = self.step(b, direction);
+                } else if new_head == b {
+                    new_head = self.step(a, direction);
+                }
+            }
+
+            // Check wall collision
+            if !self.in_arena(&new_head) {
+                self.snakes[i].alive = false;
+                new_heads.push(None);
+                continue;
+            }
+
+            // Check self collision (chasing its own tail is fine, unless the tail stays put
+            // or leaves a tron wall behind)
+            let hits_self = if self.settings.tron {
+                self.snakes[i].contains(&new_head)
+            } else {
+                self.snakes[i].occupies_after_move(&new_head)
+            };
+            if hits_self {
+                if self.self_collision_forgiveness > 0 {
+                    // Forgiven: cancel the move and keep heading the previous way
+                    self.self_collision_forgiveness -= 1;
+                    self.snakes[i].direction = previous_direction;
+                    self.snakes[i].next_direction = previous_direction;
+                    self.snakes[i].queued_turns.clear();
+                } else {
+                    self.snakes[i].alive = false;
+                }
+                new_heads.push(None);
+                continue;
+            }
+
+            // Obstacles, hazards, tron walls, and other snakes are as deadly as the snake's own body
+            if self.obstacles.contains(&new_head)
+                || self.hazards.contains(&new_head)
+                || self.tron_walls.contains(&new_head)
+                || (0..self.snakes.len()).any(|j| j != i && self.snakes[j].contains(&new_head))
+            {
+                self.snakes[i].alive = false;
+                new_heads.push(None);
+                continue;
+            }
+
+            new_heads.push(Some(new_head));
+        }
+
+        // Snakes whose heads meet in the same cell both die
+        for i in 0..new_heads.len() {
+            let Some(head) = new_heads[i] else { continue };
+            let collides = (0..new_heads.len()).any(|j| j != i && new_heads[j] == Some(head));
+            if collides {
+                self.snakes[i].alive = false;
+            }
+        }
+
+        // Move the survivors
+        for (i, new_head) in new_heads.into_iter().enumerate() {
+            match new_head {
+                Some(new_head) if self.snakes[i].alive => {
+                    self.advance_snake(i, new_head);
+                    if self.settings.near_miss && self.snakes[i].alive {
+                        self.score_near_misses(i);
+                    }
+                }
+                _ => {}
+            }
+            if self.status == GameStatus::Won {
+                return;
+            }
+        }
+
+        // The length curve follows the snakes as they grow (and shrink from hunger or poison)
+        if self.settings.speed_curve == SpeedCurve::Length {
+            self.update_speed();
+        }
+
+        // Shrinking arena: move the walls in once enough game time has passed
+        if self.settings.shrinking {
+            self.shrink_elapsed += Duration::from_millis(self.settings.delay_ms);
+            if self.shrink_elapsed >= SHRINK_INTERVAL {
+                self.shrink_elapsed -= SHRINK_INTERVAL;
+                self.shrink_arena();
+            }
+        }
+
+        self.check_game_over();
+        if self.settings.layout == Layout::Campaign && self.status == GameStatus::Playing {
+            self.check_level_goal();
+        }
+        // Time attack: the clock runs on real time, unless a replay or headless run says at
+        // which move it runs out
+        let out_of_time = match self.time_up_frame {
+            Some(frame) => self.frame >= frame,
+            None => self.time_left() == Some(Duration::ZERO),
+        };
+        if self.status == GameStatus::Playing && self.settings.time_limit.is_some() && out_of_time {
+            // The score stands; in versus mode the longer snake wins
+            self.timed_out = true;
+            if self.settings.versus {
+                let (p1, p2) = (self.snakes[0].body.len(), self.snakes[1].body.len());
+                self.winner = match p1.cmp(&p2) {
+                    std::cmp::Ordering::Greater => Some(0),
+                    std::cmp::Ordering::Less => Some(1),
+                    std::cmp::Ordering::Equal => None,
+                };
+            }
+            self.set_status(GameStatus::GameOver);
+        }
+    }
+
+    /// Time attack: play time left on the clock (None without a time limit)
+    /// The clock runs on real play time, so only pauses freeze it; slowing the game down
+    /// doesn't buy any time
+    pub fn time_left(&self) -> Option<Duration> {
+        let limit = self.settings.time_limit?;
+        Some(if self.timed_out { Duration::ZERO } else { limit.saturating_sub(self.elapsed()) })
+    }
+
+    /// Time attack: whether the game ended because the clock ran out
+    pub fn time_up(&self) -> bool {
+        self.timed_out
+    }
+
+    /// Campaign: once the level's goal score is reached, set up the next level (or win after the last)
+    /// The snakes start over from their starting positions after a fresh countdown
+    fn check_level_goal(&mut self) {
+        if self.score < LEVELS[self.level].goal {
+            return;
+        }
+        if self.level + 1 == LEVELS.len() {
+            self.set_status(GameStatus::Won);
+            return;
+        }
+
+        self.level += 1;
+        self.obstacles = LEVELS[self.level].obstacles(self.game_width, self.game_height);
+        self.snakes = starting_snakes(&self.settings, &self.obstacles);
+        self.head_trails = vec![VecDeque::new(); self.snakes.len()];
+        self.hazards.clear();
+        self.tron_walls.clear();
+        self.food.clear();
+        self.food_values.clear();
+        self.bonus = None;
+        self.poison = None;
+        self.reverser = None;
+        self.inset = 0;
+        self.shrink_elapsed = Duration::ZERO;
+        self.portals = None;
+        if self.settings.portals {
+            self.place_portals();
+        }
+        self.spawn_food();
+        self.countdown = Some(COUNTDOWN_START);
+        self.countdown_elapsed = Duration::ZERO;
+        self.countdown_tick = Instant::now();
+    }
+
+    /// Move the walls in by one cell on every side (unless the arena is already at its minimum)
+    /// Snakes caught outside hit the wall; food outside is placed again inside
+    fn shrink_arena(&mut self) {
+        let (width, height) = self.arena_size();
+        if width - 2 < MIN_ARENA_WIDTH || height - 2 < MIN_ARENA_HEIGHT {
+            return;
+        }
+        self.inset += 1;
+
+        for i in 0..self.snakes.len() {
+            if self.snakes[i].alive && !self.snakes[i].body.iter().all(|segment| self.in_arena(segment)) {
+                self.snakes[i].alive = false;
+            }
+        }
+
+        // Portals only work in pairs
+        if matches!(self.portals, Some((a, b)) if !self.in_arena(&a) || !self.in_arena(&b)) {
+            self.portals = None;
+        }
+        if matches!(self.bonus, Some((bonus, _)) if !self.in_arena(&bonus)) {
+            self.bonus = None;
+        }
+        if matches!(self.poison, Some((poison, _)) if !self.in_arena(&poison)) {
+            self.poison = None;
+        }
+        if matches!(self.reverser, Some((reverser, _)) if !self.in_arena(&reverser)) {
+            self.reverser = None;
+        }
+        let food = std::mem::take(&mut self.food);
+        self.food = food.into_iter().filter(|food| self.in_arena(food)).collect();
+        let food_values = std::mem::take(&mut self.food_values);
+        self.food_values = food_values.into_iter().filter(|(food, _)| self.in_arena(food)).collect();
+        self.spawn_food();
+    }
+
+    /// Move snake `i` onto `new_head`, eating any food there
+    fn advance_snake(&mut self, i: usize, new_head: Point) {
+        // Check if food was eaten
+        let ate_bonus = matches!(self.bonus, Some((bonus, _)) if bonus == new_head);
+        let eaten = self.food.iter().position(|food| *food == new_head);
+        let mut base_points = 10;
+        if let Some(index) = eaten {
+            self.food.swap_remove(index);
+            base_points = self.food_values.remove(&new_head).unwrap_or(base_points);
+        }
+        if eaten.is_some() || ate_bonus {
+            self.ate_food = true;
+            self.eat_flash_frames = EAT_FLASH_FRAMES;
+            if ate_bonus {
+                self.score += BONUS_POINTS;
+                self.bonus = None;
+            } else {
+                self.score += self.food_points(base_points);
+            }
+            self.update_speed();
+            self.snakes[i].pending_growth += self.settings.growth_per_food;
+            // Length cap: growth stops at the limit, and from there the tail keeps moving
+            if self.settings.max_length > 0 {
+                let snake = &mut self.snakes[i];
+                let room = (self.settings.max_length as usize).saturating_sub(snake.body.len());
+                snake.pending_growth = snake.pending_growth.min(room as u32);
+            }
+
+            // Trail mode: the spot turns deadly once the snake has passed over it
+            if self.settings.hazard_trail {
+                self.hazards.push(new_head);
+            }
+        }
+
+        // Remember the cell the head is leaving (before the tail goes, which may be the head)
+        if let Some(&head) = self.snakes[i].body.front() {
+            let trail = &mut self.head_trails[i];
+            trail.push_front(head);
+            trail.truncate(HEAD_TRAIL_LENGTH);
+        }
+
+        // Move snake: the tail stays put while the snake still has growing to do
+        // (it goes first, so the head can take over the cell it leaves)
+        if self.snakes[i].pending_growth > 0 {
+            self.snakes[i].pending_growth -= 1;
+        } else if let Some(tail) = self.snakes[i].pop_back() {
+            self.leave_tron_wall(tail);
+        }
+        self.snakes[i].push_front(new_head);
+
+        // Poison cuts segments off the tail; a snake that would be left with nothing dies
+        if matches!(self.poison, Some((poison, _)) if poison == new_head) {
+            self.poison = None;
+            self.score = self.score.saturating_sub(POISON_PENALTY);
+            self.update_speed();
+            if self.snakes[i].body.len() <= POISON_SHRINK {
+                self.snakes[i].alive = false;
+                return;
+            }
+            for _ in 0..POISON_SHRINK {
+                if let Some(cell) = self.snakes[i].pop_back() {
+                    self.leave_tron_wall(cell);
+                }
+            }
+        }
+
+        // The reverse powerup turns the controls around for a while
+        if matches!(self.reverser, Some((reverser, _)) if reverser == new_head) {
+            self.reverser = None;
+            self.controls_inverted_until_frame = self.frame + REVERSE_EFFECT_FRAMES;
+        }
+
+        // Hunger: food resets the timer; running out costs a tail segment, and the last one kills
+        if self.settings.hunger_limit > 0 {
+            if eaten.is_some() || ate_bonus {
+                self.snakes[i].hunger = self.settings.hunger_limit;
+            } else {
+                self.snakes[i].hunger = self.snakes[i].hunger.saturating_sub(1);
+            }
+            if self.snakes[i].hunger == 0 {
+                if self.snakes[i].body.len() <= 1 {
+                    self.snakes[i].alive = false;
+                    return;
+                }
+                if let Some(cell) = self.snakes[i].pop_back() {
+                    self.leave_tron_wall(cell);
+                }
+                self.snakes[i].hunger = self.settings.hunger_limit;
+            }
+        }
+
+        // Snakes cover every free cell: nothing left to spawn food on
+        if self.free_cell_count() == 0 {
+            self.set_status(GameStatus::Won);
+        } else if eaten.is_some() {
+            self.spawn_food();
+            self.maybe_spawn_bonus();
+            self.maybe_spawn_poison();
+            self.maybe_spawn_reverser();
+        }
+    }
+
+    /// True if hunger mode is on and the player's snake is close to losing a segment
+    pub fn is_hungry(&self, player: usize) -> bool {
+        let limit = self.settings.hunger_limit;
+        limit > 0 && self.snakes[player].hunger <= limit / HUNGER_WARNING_DIVISOR
+    }
+
+    /// Tron mode: turn a cell a tail has just left into a permanent wall
+    /// (a hazard there is already deadly, so it isn't counted twice)
+    fn leave_tron_wall(&mut self, cell: Point) {
+        if self.settings.tron && !self.hazards.contains(&cell) {
+            self.tron_walls.insert(cell);
+        }
+    }
+
+    /// End the game once player 1 dies, or in versus mode once at most one snake is left
+    fn check_game_over(&mut self) {
+        let alive: Vec<usize> = (0..self.snakes.len()).filter(|&i| self.snakes[i].alive).collect();
+
+        if self.settings.versus {
+            if alive.len() <= 1 {
+                self.winner = alive.first().copied();
+                self.set_status(self.death_status());
+            }
+        } else if alive.is_empty() {
+            self.set_status(self.death_status());
+        }
+    }
+
+    /// Status to enter when the game is lost: the death animation if there is one
+    fn death_status(&self) -> GameStatus {
+        match self.death_frames {
+            0 => GameStatus::GameOver,
+            frames_left => GameStatus::Dying { frames_left },
+        }
+    }
+
+    /// Cut the death animation short; the next update ends the game
+    pub fn skip_death_animation(&mut self) {
+        if let GameStatus::Dying { frames_left } = &mut self.status {
+            *frames_left = 1;
+        }
+    }
+
+    /// Points for a regular food worth `base_points` eaten now: that, or more when it keeps a
+    /// combo going (raising the combo level for the next one)
+    fn food_points(&mut self, base_points: u32) -> u32 {
+        if !self.settings.combos {
+            return base_points;
+        }
+        if self.frames_since_last_food <= COMBO_WINDOW_FRAMES {
+            self.combo_level = (self.combo_level + 1).min(COMBO_MAX_LEVEL);
+        }
+        self.frames_since_last_food = 0;
+        base_points + self.combo_level * COMBO_STEP_POINTS
+    }
+
+    /// Near misses: score the segments of snake `i`'s body (its neck aside) right next to its head
+    fn score_near_misses(&mut self, i: usize) {
+        let snake = &self.snakes[i];
+        let Some(&head) = snake.body.front() else { return };
+        let close = Direction::ALL[..4]
+            .iter()
+            .filter(|&&direction| {
+                let cell = self.step(head, direction);
+                snake.body.iter().skip(2).any(|segment| *segment == cell)
+            })
+            .count() as u32;
+        if close > 0 {
+            self.style += close;
+            self.score += close * NEAR_MISS_POINTS;
+            self.update_speed();
+        }
+    }
+
+    /// Move the regular food to new cells for REROLL_COST points (the score stops at zero)
+    /// Only while the snakes are moving (not before the first move or during a countdown), and
+    /// at most REROLLS_PER_GAME times; returns false if refused
+    pub fn reroll_food(&mut self) -> bool {
+        if !self.will_move() || self.rerolls_left == 0 || self.food.is_empty() {
+            return false;
+        }
+        self.rerolls_left -= 1;
+        self.food.clear();
+        self.food_values.clear();
+        self.spawn_food();
+        self.score = self.score.saturating_sub(REROLL_COST);
+        self.update_speed();
+        true
+    }
+
+    /// Combos: what the next food is worth compared to a plain 10 points (1.0 without a combo)
+    pub fn combo_multiplier(&self) -> f32 {
+        let next_level = if self.frames_since_last_food < COMBO_WINDOW_FRAMES {
+            (self.combo_level + 1).min(COMBO_MAX_LEVEL)
+        } else {
+            0
+        };
+        (10 + next_level * COMBO_STEP_POINTS) as f32 / 10.0
+    }
+
+    /// Whether a snake has grown to the length cap (never without one)
+    pub fn at_max_length(&self, player: usize) -> bool {
+        let max_length = self.settings.max_length as usize;
+        max_length > 0 && self.snakes[player].body.len() >= max_length
+    }
+
+    /// Speed the game up (shorter delay) or slow it down by one step, from the next move on
+    /// Lasts until the game is restarted; the score-based speed-up still applies on top
+    pub fn adjust_speed(&mut self, faster: bool) {
+        self.speed_adjust_ms += if faster { -SPEED_ADJUST_STEP_MS } else { SPEED_ADJUST_STEP_MS };
+        self.update_speed();
+    }
+
+    /// Recompute the frame duration from the score or length (progressive speed-up) and
+    /// manual adjustments
+    fn update_speed(&mut self) {
+        let start_ms = self.settings.delay_ms;
+
+        // Never speed up past the floor or slow down past the ceiling, but don't force
+        // a starting delay outside them back in either
+        let floor_ms = self.settings.min_frame_ms.min(start_ms);
+        let ceiling_ms = self.settings.max_frame_ms.max(start_ms);
+        let base_ms = match self.settings.speed_curve {
+            SpeedCurve::Score => {
+                let steps = (self.score / SPEED_UP_SCORE_INTERVAL) as u64;
+                start_ms.saturating_sub(steps * SPEED_UP_STEP_MS).max(floor_ms)
+            }
+            SpeedCurve::Length => {
+                // Straight line from the starting length to the "full" length
+                let longest = self.snakes.iter().map(|snake| snake.body.len()).max().unwrap_or(0) as u64;
+                let start_length = self.settings.start_length as u64;
+                let full = (self.game_width * self.game_height * LENGTH_CURVE_FULL_PERCENT / 100) as u64;
+                let span = full.saturating_sub(start_length).max(1);
+                let grown = longest.saturating_sub(start_length).min(span);
+                start_ms - (start_ms - floor_ms) * grown / span
+            }
+        };
+
+        // Keep the adjustment within range so pressing past a limit doesn't pile up
+        let adjusted_ms = (base_ms as i64 + self.speed_adjust_ms).clamp(floor_ms as i64, ceiling_ms as i64);
+        self.speed_adjust_ms = adjusted_ms - base_ms as i64;
+        self.frame_duration = Duration::from_millis(adjusted_ms as u64);
+    }
+
+    /// Whether a pause can only have come from the terminal being too small
+    fn resize_held_pause(&self) -> bool {
+        self.waiting_for_start || (!self.settings.allow_pause && !self.confirm_quit)
+    }
+
+    /// Re-center after a terminal resize; pauses the game while the field doesn't fit
+    pub fn relocate(&mut self, offset_x: i32, offset_y: i32, fits: bool) {
+        self.offset_x = offset_x;
+        self.offset_y = offset_y;
+        self.terminal_too_small = !fits;
+
+        if !fits && self.status == GameStatus::Playing {
+            self.set_status(GameStatus::Paused);
+        } else if fits && self.status == GameStatus::Paused && self.resize_held_pause() {
+            // Players can't pause before starting (or at all in hardcore runs), so this
+            // pause came from the resize; the waiting-for-start gate still keeps the snake still
+            self.set_status(GameStatus::Playing);
+        }
+    }
+
+    /// Start a fresh game with the same settings and window offsets
+    pub fn restart(&mut self) {
+        let high_score = self.high_score;
+        let auto_pause_frames = self.auto_pause_frames;
+        let death_frames = self.death_frames;
+        let rewind_limit = self.rewind_limit;
+        let fixed_seed = self.fixed_seed;
+        let no_wait = self.no_wait;
+        let map = self.map.take();
+        let seed = if fixed_seed { self.seed } else { rand::random() };
+        *self = GameState::build(self.settings, self.offset_x, self.offset_y, seed);
+        self.fixed_seed = fixed_seed;
+        self.high_score = high_score;
+        self.auto_pause_frames = auto_pause_frames;
+        self.death_frames = death_frames;
+        self.rewind_limit = rewind_limit;
+        if let Some(map) = map {
+            self.use_map(map);
+        }
+        if no_wait {
+            self.skip_start_wait();
+        }
+    }
+
+    /// Start moving right away in the starting direction, with no waiting for a first
+    /// key press or countdown; restarts start the same way
+    pub fn skip_start_wait(&mut self) {
+        self.no_wait = true;
+        self.waiting_for_start = false;
+        self.start_time = Instant::now();
+    }
+
+    /// Play on a user-made map: its walls, start, and food replace the layout's
+    /// The map must be the size of the board; restarts set it up the same way again
+    pub fn use_map(&mut self, map: Map) {
+        debug_assert_eq!((map.width, map.height), (self.game_width, self.game_height));
+        let open = |cell: &Point| {
+            cell.x >= 0 && cell.x < map.width && cell.y >= 0 && cell.y < map.height && !map.obstacles.contains(cell)
+        };
+        let open_run = |(dx, dy): (i32, i32)| {
+            (1..)
+                .map(|i| Point {
+                    x: map.start.x + dx * i,
+                    y: map.start.y + dy * i,
+                })
+                .take_while(open)
+                .count()
+        };
+
+        // Head for the longest open stretch that leaves room for the body behind, so the
+        // snake doesn't start out facing a wall; the body trails as far as the walls allow
+        let trailing = self.settings.start_length.saturating_sub(1) as usize;
+        let room = |direction: &Direction| {
+            let (dx, dy) = direction.offset();
+            (open_run((-dx, -dy)) >= trailing, open_run((dx, dy)))
+        };
+        let directions = [Direction::Right, Direction::Left, Direction::Down, Direction::Up];
+        let direction = directions.into_iter().rev().max_by_key(room).unwrap();
+        let (dx, dy) = direction.offset();
+        let length = 1 + open_run((-dx, -dy)).min(trailing);
+        let mut snake = Snake::new(map.start, direction, length);
+        snake.hunger = self.settings.hunger_limit;
+
+        self.snakes = vec![snake];
+        self.head_trails = vec![VecDeque::new()];
+        self.obstacles = map.obstacles.clone();
+        self.food.clear();
+        self.food_values.clear();
+        for &cell in &map.food {
+            if !self.snakes[0].contains(&cell) {
+                self.place_food(cell);
+            }
+        }
+        self.spawn_food();
+        self.portals = None;
+        if self.settings.portals {
+            self.place_portals();
+        }
+        self.map = Some(map);
+    }
+
+    /// Remember the state before a move, dropping the oldest once rewind_limit are kept
+    fn save_snapshot(&mut self) {
+        if self.rewind_limit == 0 {
+            return;
+        }
+        // Snapshots don't carry their own history
+        let history = std::mem::take(&mut self.history);
+        let snapshot = self.clone();
+        self.history = history;
+
+        if self.history.len() >= self.rewind_limit {
+            self.history.pop_front();
+        }
+        self.history.push_back(snapshot);
+    }
+
+    /// Go back `moves` moves (or as far as the history reaches) and pause there
+    /// The high score and the window placement stay as they are now
+    /// Returns false if there is nothing to go back to
+    pub fn rewind(&mut self, moves: usize) -> bool {
+        let keep = self.history.len().saturating_sub(moves.max(1));
+        let Some(snapshot) = self.history.drain(keep..).next() else {
+            return false;
+        };
+
+        let history = std::mem::take(&mut self.history);
+        let (high_score, fixed_seed) = (self.high_score, self.fixed_seed);
+        let (offset_x, offset_y, too_small) = (self.offset_x, self.offset_y, self.terminal_too_small);
+        *self = snapshot;
+        self.history = history;
+        self.high_score = high_score;
+        self.fixed_seed = fixed_seed;
+        self.offset_x = offset_x;
+        self.offset_y = offset_y;
+        self.terminal_too_small = too_small;
+        self.set_status(GameStatus::Paused);
+        true
+    }
+
+    /// Pause a running game, or resume a paused one (finished games stay finished)
+    pub fn toggle_pause(&mut self) {
+        let status = match self.status {
+            GameStatus::Playing => GameStatus::Paused,
+            GameStatus::Paused => GameStatus::Playing,
+            GameStatus::Dying { frames_left } => GameStatus::Dying { frames_left },
+            GameStatus::GameOver => GameStatus::GameOver,
+            GameStatus::Won => GameStatus::Won,
+        };
+        self.set_status(status);
+    }
+
+    /// Change the status, keeping the play clock in step (paused time doesn't count)
+    fn set_status(&mut self, status: GameStatus) {
+        let now = Instant::now();
+        if status == GameStatus::Paused {
+            self.pause_started.get_or_insert(now);
+        } else if let Some(pause_started) = self.pause_started.take() {
+            self.paused_time += now - pause_started;
+            self.countdown_tick = now;
+        }
+        if matches!(status, GameStatus::Dying { .. } | GameStatus::GameOver | GameStatus::Won) {
+            self.ended_at.get_or_insert(now);
+        }
+        self.auto_paused = false;
+        self.status = status;
+    }
+
+    /// Time actually spent playing: from the first move, minus pauses, up to the end
+    pub fn elapsed(&self) -> Duration {
+        if self.waiting_for_start || (self.countdown.is_some() && self.frame == 0) {
+            return Duration::ZERO;
+        }
+        let until = self.ended_at.or(self.pause_started).unwrap_or_else(Instant::now);
+        until
+            .saturating_duration_since(self.start_time)
+            .saturating_sub(self.paused_time)
+    }
+
+    /// True once the game has ended, either by dying or by winning
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status, GameStatus::GameOver | GameStatus::Won)
+    }
+}
+
+/// Snakes in their starting positions: one in the center, or one per player in versus mode
+/// Each trails `start_length` segments behind its head, fewer if the board edge or an
+/// obstacle comes first (but always at least the head)
+fn starting_snakes(settings: &GameSettings, obstacles: &[Point]) -> Vec<Snake> {
+    let heads = if settings.versus {
+        // Player 1 starts on the left heading right, player 2 on the right heading left
+        vec![
+            (
+                Point {
+                    x: settings.width / 4,
+                    y: settings.height / 3,
+                },
+                Direction::Right,
+            ),
+            (
+                Point {
+                    x: settings.width - 1 - settings.width / 4,
+                    y: settings.height - 1 - settings.height / 3,
+                },
+                Direction::Left,
+            ),
+        ]
+    } else {
+        // Start snake in the center
+        vec![(
+            Point {
+                x: settings.width / 2,
+                y: settings.height / 2,
+            },
+            Direction::Right,
+        )]
+    };
+
+    heads
+        .into_iter()
+        .map(|(head, direction)| {
+            let (dx, dy) = direction.offset();
+            let fits = |i: i32| {
+                let cell = Point {
+                    x: head.x - dx * i,
+                    y: head.y - dy * i,
+                };
+                cell.x >= 0 && cell.x < settings.width && !obstacles.contains(&cell)
+            };
+            let length = 1 + (1..settings.start_length as i32).take_while(|&i| fits(i)).count();
+
+            let mut snake = Snake::new(head, direction, length);
+            snake.hunger = settings.hunger_limit;
+            snake
+        })
+        .collect()
+}
+
+/// Number of moves at the given delay that add up to `span` (at least one; zero if `span` is zero)
+pub fn frames_for(span: Duration, delay_ms: u64) -> u32 {
+    if span.is_zero() {
+        return 0;
+    }
+    (span.as_millis() as u64 / delay_ms.max(1)).clamp(1, u32::MAX as u64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_game(width: i32, height: i32, wrap_walls: bool) -> GameState {
+        let settings = GameSettings {
+            wrap_walls,
+            ..GameSettings::new(width, height, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.waiting_for_start = false;
+        game
+    }
+
+    fn place_snake(game: &mut GameState, segments: &[(i32, i32)]) {
+        game.snakes[0].set_body(segments.iter().map(|&(x, y)| Point { x, y }));
+    }
+
+    #[test]
+    fn mode_names_list_the_scoring_options() {
+        let classic = GameSettings::new(30, 20, 100);
+        assert_eq!(classic.mode_name(), CLASSIC_MODE);
+        assert_eq!(GameSettings { delay_ms: 50, assist: true, ..classic }.mode_name(), CLASSIC_MODE);
+
+        let settings = GameSettings {
+            wrap_walls: true,
+            layout: Layout::Blocks,
+            tron: true,
+            time_limit: Some(Duration::from_secs(60)),
+            ..classic
+        };
+        assert_eq!(settings.mode_name(), "wrap+blocks+tron+time60s");
+    }
+
+    #[test]
+    fn near_misses_score_for_passing_alongside_the_body() {
+        let settings = GameSettings {
+            near_miss: true,
+            ..GameSettings::new(20, 10, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.waiting_for_start = false;
+        game.food = vec![Point { x: 0, y: 0 }];
+
+        // A U-turn: coming back along the row the snake just ran down brushes past it
+        place_snake(&mut game, &[(8, 3), (7, 3), (6, 3), (5, 3)]);
+        game.set_player_direction(0, Direction::Down);
+        game.update();
+        assert_eq!((game.style, game.score), (0, 0));
+        game.set_player_direction(0, Direction::Left);
+        game.update();
+        assert_eq!(game.snake().head(), Point { x: 7, y: 4 });
+        assert_eq!((game.style, game.score), (1, NEAR_MISS_POINTS));
+
+        // The segment it passed has moved on by the next step
+        game.update();
+        assert_eq!(game.style, 1);
+
+        // A straight run brushes past nothing
+        let mut plain = test_game(20, 10, false);
+        plain.update();
+        assert_eq!(plain.style, 0);
+    }
+
+    #[test]
+    fn food_on_a_nearly_full_board_goes_on_one_of_the_open_cells() {
+        let mut game = test_game(10, 5, false);
+        // The snake winds through every row but leaves the last three cells of the bottom one
+        let segments: Vec<(i32, i32)> = (0..5)
+            .flat_map(|y| (0..10).map(move |x| if y % 2 == 1 { (9 - x, y) } else { (x, y) }))
+            .filter(|&(x, y)| y < 4 || x < 7)
+            .collect();
+        place_snake(&mut game, &segments);
+        game.food.clear();
+
+        let open: HashSet<Point> = (7..10).map(|x| Point { x, y: 4 }).collect();
+        let picked: HashSet<Point> = (0..50).filter_map(|_| game.random_free_cell()).collect();
+        assert_eq!(picked, open);
+
+        game.spawn_food();
+        assert!(open.contains(&game.food[0]));
+    }
+
+    #[test]
+    fn a_completely_full_board_gets_no_food() {
+        let mut game = test_game(10, 5, false);
+        let segments: Vec<(i32, i32)> = (0..5)
+            .flat_map(|y| (0..10).map(move |x| if y % 2 == 1 { (9 - x, y) } else { (x, y) }))
+            .collect();
+        place_snake(&mut game, &segments);
+        // A cell counted twice mustn't push the free-cell count below zero
+        game.obstacles = vec![Point { x: 0, y: 0 }];
+        game.food.clear();
+
+        assert_eq!(game.free_cell_count(), 0);
+        assert_eq!(game.random_free_cell(), None);
+        game.spawn_food();
+        assert!(game.food.is_empty());
+    }
+
+    #[test]
+    fn rerolls_move_the_food_for_a_few_points() {
+        let mut game = test_game(20, 10, false);
+        game.score = 7;
+        let food = Point { x: 0, y: 0 };
+        game.food = vec![food];
+
+        assert!(game.reroll_food());
+        assert_eq!(game.food.len(), 1);
+        assert!(!game.snake().contains(&game.food[0]));
+        assert_eq!((game.score, game.rerolls_left), (7 - REROLL_COST, REROLLS_PER_GAME - 1));
+
+        // The score can't go below zero, and the rerolls run out
+        while game.reroll_food() {}
+        assert_eq!((game.score, game.rerolls_left), (0, 0));
+
+        // A new game gets a fresh allowance, but can't spend it before the snake sets off,
+        // during the countdown, or once it's over
+        game.restart();
+        assert_eq!(game.rerolls_left, REROLLS_PER_GAME);
+        assert!(!game.reroll_food());
+        game.start_countdown();
+        assert!(game.countdown.is_some());
+        assert!(!game.reroll_food());
+        game.status = GameStatus::GameOver;
+        assert!(!game.reroll_food());
+        assert_eq!((game.score, game.rerolls_left), (0, REROLLS_PER_GAME));
+    }
+
+    #[test]
+    fn skipping_the_start_wait_moves_at_once_and_survives_restarts() {
+        let mut game = test_game(20, 10, false);
+        game.skip_start_wait();
+        let head = game.snake().head();
+        game.update();
+        assert_eq!(game.snake().head(), game.step(head, Direction::Right));
+
+        game.restart();
+        assert!(!game.waiting_for_start);
+        assert!(game.will_move());
+    }
+
+    #[test]
+    fn fixed_seed_restarts_replay_the_same_food() {
+        let settings = GameSettings::new(20, 10, 100);
+        let mut game = GameState::new_seeded(settings, 0, 0, 42).unwrap();
+        game.fixed_seed = true;
+        let food = game.food.clone();
+        game.restart();
+        assert_eq!((game.seed, &game.food), (42, &food));
+        assert!(game.fixed_seed);
+
+        game.fixed_seed = false;
+        game.restart();
+        assert_ne!(game.seed, 42);
+    }
+
+    #[test]
+    fn head_trail_remembers_the_last_few_head_cells() {
+        let mut game = test_game(20, 10, false);
+        let start = game.snake().head();
+        for _ in 0..HEAD_TRAIL_LENGTH + 2 {
+            game.update();
+        }
+        let expected: Vec<Point> =
+            (0..HEAD_TRAIL_LENGTH as i32).map(|back| Point { x: game.snake().head().x - 1 - back, y: start.y }).collect();
+        assert_eq!(game.head_trails[0], expected);
+
+        game.restart();
+        assert!(game.head_trails[0].is_empty());
+    }
+
+    #[test]
+    fn danger_ahead_predicts_deadly_moves() {
+        let mut game = test_game(10, 5, false);
+        place_snake(&mut game, &[(9, 2), (8, 2), (7, 2)]);
+        assert_eq!(game.danger_ahead(0), Some(Point { x: 10, y: 2 }));
+        game.set_player_direction(0, Direction::Down);
+        assert_eq!(game.danger_ahead(0), None);
+
+        // Turning into its own body is deadly, unless the bite would be forgiven
+        place_snake(&mut game, &[(3, 2), (3, 3), (2, 3), (2, 2), (1, 2)]);
+        game.snakes[0].direction = Direction::Up;
+        game.snakes[0].next_direction = Direction::Up;
+        game.set_player_direction(0, Direction::Left);
+        assert_eq!(game.danger_ahead(0), Some(Point { x: 2, y: 2 }));
+        game.self_collision_forgiveness = 1;
+        assert_eq!(game.danger_ahead(0), None);
+    }
+
+    #[test]
+    fn wall_collision_ends_game() {
+        let mut game = test_game(10, 5, false);
+        place_snake(&mut game, &[(9, 2), (8, 2), (7, 2)]);
+        game.food = vec![Point { x: 0, y: 0 }];
+
+        game.update();
+
+        assert_eq!(game.status, GameStatus::GameOver);
+    }
+
+    #[test]
+    fn death_animation_plays_before_game_over() {
+        let mut game = test_game(10, 5, false);
+        game.death_frames = 3;
+        place_snake(&mut game, &[(9, 2), (8, 2), (7, 2)]);
+        game.food = vec![Point { x: 0, y: 0 }];
+
+        game.update();
+        assert_eq!(game.status, GameStatus::Dying { frames_left: 3 });
+        assert!(!game.is_finished());
+
+        // Direction input has no effect while dying
+        game.set_player_direction(0, Direction::Up);
+        game.update();
+        assert_eq!(game.status, GameStatus::Dying { frames_left: 2 });
+        assert_eq!(game.snake().head(), Point { x: 9, y: 2 });
+
+        // A key press cuts it short: the next update ends the game
+        game.skip_death_animation();
+        game.update();
+        assert_eq!(game.status, GameStatus::GameOver);
+        assert_eq!(game.frame, 1);
+    }
+
+    #[test]
+    fn wrap_walls_reenter_on_opposite_edge() {
+        let mut game = test_game(10, 5, true);
+        place_snake(&mut game, &[(9, 2), (8, 2), (7, 2)]);
+        game.food = vec![Point { x: 0, y: 0 }];
+
+        game.update();
+
+        assert_eq!(game.status, GameStatus::Playing);
+        assert_eq!(game.snake().body.front(), Some(&Point { x: 0, y: 2 }));
+    }
+
+    #[test]
+    fn doorways_lead_to_the_opposite_wall() {
+        let settings = GameSettings {
+            doorways: true,
+            ..GameSettings::new(10, 5, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.waiting_for_start = false;
+        game.food = vec![Point { x: 0, y: 0 }];
+        assert_eq!(game.doorways()[3], Point { x: 10, y: 2 });
+
+        // Out through the right-hand doorway, in through the left one
+        place_snake(&mut game, &[(9, 2), (8, 2), (7, 2)]);
+        game.update();
+        assert_eq!(game.status, GameStatus::Playing);
+        assert_eq!(game.snake().head(), Point { x: 0, y: 2 });
+
+        // Anywhere else the wall is still solid
+        place_snake(&mut game, &[(9, 1), (8, 1), (7, 1)]);
+        game.update();
+        assert_eq!(game.status, GameStatus::GameOver);
+    }
+
+    #[test]
+    fn self_collision_ends_game() {
+        let mut game = test_game(10, 5, false);
+        // Head at (2,2) moving right into its own body at (3,2)
+        place_snake(&mut game, &[(2, 2), (2, 3), (3, 3), (3, 2), (3, 1)]);
+        game.food = vec![Point { x: 0, y: 0 }];
+
+        game.update();
+
+        assert_eq!(game.status, GameStatus::GameOver);
+    }
+
+    #[test]
+    fn forgiven_self_collision_cancels_the_move() {
+        let mut game = test_game(10, 5, false);
+        place_snake(&mut game, &[(2, 2), (2, 3), (3, 3), (3, 2), (3, 1)]);
+        game.food = vec![Point { x: 0, y: 0 }];
+        game.snakes[0].direction = Direction::Up;
+        game.snakes[0].next_direction = Direction::Right;
+        game.self_collision_forgiveness = 1;
+
+        game.update();
+
+        assert_eq!(game.status, GameStatus::Playing);
+        assert_eq!(game.self_collision_forgiveness, 0);
+        assert_eq!(game.snake().body.front(), Some(&Point { x: 2, y: 2 }));
+        assert_eq!(game.snake().direction, Direction::Up);
+    }
+
+    #[test]
+    fn obstacle_collision_ends_game() {
+        let mut game = test_game(10, 5, false);
+        place_snake(&mut game, &[(5, 2), (4, 2), (3, 2)]);
+        game.obstacles = vec![Point { x: 6, y: 2 }];
+        game.food = vec![Point { x: 0, y: 0 }];
+
+        game.update();
+
+        assert_eq!(game.status, GameStatus::GameOver);
+    }
+
+    #[test]
+    fn eating_food_grows_snake_and_scores() {
+        let mut game = test_game(10, 5, false);
+        place_snake(&mut game, &[(5, 2), (4, 2), (3, 2)]);
+        game.food = vec![Point { x: 6, y: 2 }];
+
+        game.update();
+
+        assert_eq!(game.snake().body.len(), 4);
+        assert_eq!(game.score, 10);
+        assert!(game.food.iter().all(|food| !game.snake().contains(food)));
+    }
+
+    #[test]
+    fn shrinking_terminal_pauses_game() {
+        let mut game = test_game(10, 5, false);
+
+        game.relocate(3, 4, false);
+        assert_eq!(game.status, GameStatus::Paused);
+        assert!(game.terminal_too_small);
+
+        game.relocate(5, 6, true);
+        assert_eq!((game.offset_x, game.offset_y), (5, 6));
+        assert!(!game.terminal_too_small);
+        assert_eq!(game.status, GameStatus::Paused); // Player resumes with P
+    }
+
+    #[test]
+    fn same_seed_gives_same_food_sequence() {
+        let settings = GameSettings::new(20, 10, 100);
+        let mut a = GameState::new_seeded(settings, 0, 0, 42).unwrap();
+        let mut b = GameState::new_seeded(settings, 0, 0, 42).unwrap();
+
+        for _ in 0..10 {
+            assert_eq!(a.food, b.food);
+            a.food.clear();
+            b.food.clear();
+            a.spawn_food();
+            b.spawn_food();
+        }
+    }
+
+    #[test]
+    fn bonus_food_scores_and_expires() {
+        let mut game = test_game(10, 5, false);
+        place_snake(&mut game, &[(5, 2), (4, 2), (3, 2)]);
+        game.food = vec![Point { x: 0, y: 0 }];
+        game.bonus = Some((Point { x: 6, y: 2 }, 5));
+
+        game.update();
+        assert_eq!(game.score, BONUS_POINTS);
+        assert_eq!(game.snake().body.len(), 4);
+        assert_eq!(game.bonus, None);
+
+        game.bonus = Some((Point { x: 0, y: 4 }, 1));
+        game.update();
+        assert_eq!(game.bonus, None);
+    }
+
+    #[test]
+    fn poison_shrinks_the_snake_and_costs_points() {
+        let mut game = test_game(10, 5, false);
+        place_snake(&mut game, &[(5, 2), (4, 2), (3, 2), (2, 2), (1, 2)]);
+        game.food = vec![Point { x: 0, y: 0 }];
+        game.score = 50;
+        game.poison = Some((Point { x: 6, y: 2 }, 5));
+
+        game.update();
+        assert_eq!(game.score, 50 - POISON_PENALTY);
+        assert_eq!(game.snake().body.len(), 2);
+        assert_eq!(game.snake().head(), Point { x: 6, y: 2 });
+        assert_eq!(game.poison, None);
+        assert_eq!(game.status, GameStatus::Playing);
+
+        // Too short to lose three more segments
+        game.poison = Some((Point { x: 7, y: 2 }, 5));
+        game.update();
+        assert_eq!(game.score, 50 - 2 * POISON_PENALTY);
+        assert_eq!(game.status, GameStatus::GameOver);
+    }
+
+    #[test]
+    fn poison_spawns_away_from_the_head() {
+        let settings = GameSettings {
+            poison_chance: 100,
+            ..GameSettings::new(20, 10, 100)
+        };
+        let mut game = GameState::new_seeded(settings, 0, 0, 7).unwrap();
+        for _ in 0..20 {
+            game.poison = None;
+            game.maybe_spawn_poison();
+            let (poison, _) = game.poison.unwrap();
+            let head = game.snake().head();
+            assert!((head.x - poison.x).abs().max((head.y - poison.y).abs()) >= POISON_MIN_HEAD_DISTANCE);
+            assert!(!game.food.contains(&poison));
+        }
+    }
+
+    #[test]
+    fn tron_mode_leaves_walls_behind_the_tail() {
+        let settings = GameSettings {
+            tron: true,
+            ..GameSettings::new(10, 5, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.waiting_for_start = false;
+        place_snake(&mut game, &[(3, 2), (2, 2), (1, 2), (1, 1)]);
+        game.food = vec![Point { x: 9, y: 4 }];
+        let free_before = game.free_cell_count();
+
+        game.update();
+        assert!(game.tron_walls.contains(&Point { x: 1, y: 1 }));
+        assert_eq!(game.snake().body.len(), 4);
+        assert_eq!(game.free_cell_count(), free_before - 1);
+
+        // The cell the tail is about to leave is as deadly as the wall it turns into
+        game.tron_walls.clear();
+        place_snake(&mut game, &[(1, 2), (2, 2), (2, 1), (1, 1)]);
+        game.snakes[0].direction = Direction::Up;
+        game.snakes[0].next_direction = Direction::Up;
+        game.update();
+        assert_eq!(game.status, GameStatus::GameOver);
+    }
+
+    #[test]
+    fn occupied_cells_stay_in_sync_with_the_body() {
+        let settings = GameSettings {
+            wrap_walls: true,
+            growth_per_food: 2,
+            ..GameSettings::new(20, 10, 100)
+        };
+        let mut game = GameState::new_seeded(settings, 0, 0, 3).unwrap();
+        game.waiting_for_start = false;
+        let turns = [Direction::Down, Direction::Left, Direction::Up, Direction::Right];
+
+        for step in 0..400 {
+            if step % 7 == 0 {
+                game.set_player_direction(0, turns[step / 7 % turns.len()]);
+            }
+            if step == 150 {
+                game.poison = Some((game.step(game.snake().head(), game.snake().next_direction), 5));
+            }
+            game.update();
+            if game.is_finished() {
+                break;
+            }
+
+            let snake = game.snake();
+            assert_eq!(snake.cells.len(), snake.body.len());
+            assert!(snake.body.iter().all(|segment| snake.cells.contains(segment)));
+        }
+        assert!(game.frame > 20);
+    }
+
+    #[test]
+    fn hunger_costs_segments_until_the_snake_eats() {
+        let settings = GameSettings {
+            wrap_walls: true,
+            hunger_limit: 4,
+            ..GameSettings::new(20, 10, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.waiting_for_start = false;
+        place_snake(&mut game, &[(3, 2), (2, 2), (1, 2)]);
+        game.food = vec![Point { x: 0, y: 9 }];
+        assert_eq!(game.snake().hunger, 4);
+
+        for _ in 0..3 {
+            game.update();
+        }
+        assert_eq!(game.snake().hunger, 1);
+        assert!(game.is_hungry(0));
+        game.update();
+        assert_eq!(game.snake().body.len(), 2);
+        assert_eq!(game.snake().hunger, 4);
+
+        // Eating fills the snake up again
+        game.snakes[0].hunger = 2;
+        game.food = vec![game.step(game.snake().head(), Direction::Right)];
+        game.update();
+        assert_eq!(game.snake().hunger, 4);
+        assert!(!game.is_hungry(0));
+
+        // A snake down to its head starves
+        place_snake(&mut game, &[(3, 2)]);
+        game.food = vec![Point { x: 0, y: 9 }];
+        game.bonus = None;
+        game.snakes[0].hunger = 1;
+        game.update();
+        assert_eq!(game.status, GameStatus::GameOver);
+    }
+
+    #[test]
+    fn start_length_is_cut_short_by_the_board_and_walls() {
+        let body_length = |start_length, versus, layout| {
+            let settings = GameSettings {
+                start_length,
+                versus,
+                layout,
+                ..GameSettings::new(20, 10, 100)
+            };
+            let game = GameState::new(settings, 0, 0).unwrap();
+            game.snakes.iter().map(|snake| snake.body.len()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(body_length(1, false, Layout::Open), vec![1]);
+        assert_eq!(body_length(8, false, Layout::Open), vec![8]);
+        assert_eq!(body_length(50, false, Layout::Open), vec![11]); // Head at x=10 back to the left edge
+        assert_eq!(body_length(50, true, Layout::Open), vec![6, 6]);
+
+        // A pillar at x=1 across the middle row of the third campaign level stops it just short
+        let pillars = LEVELS[2].obstacles(20, 10);
+        let snakes = starting_snakes(
+            &GameSettings {
+                start_length: 50,
+                ..GameSettings::new(20, 10, 100)
+            },
+            &pillars,
+        );
+        assert_eq!(snakes[0].body.len(), 9);
+        assert!(snakes[0].body.iter().all(|segment| !pillars.contains(segment)));
+    }
+
+    #[test]
+    fn direction_to_handles_wrapped_neighbours() {
+        let p = Point { x: 0, y: 2 };
+        assert_eq!(p.direction_to(&Point { x: 1, y: 2 }, 10, 5), Some(Direction::Right));
+        assert_eq!(p.direction_to(&Point { x: 9, y: 2 }, 10, 5), Some(Direction::Left));
+        assert_eq!(p.direction_to(&Point { x: 0, y: 1 }, 10, 5), Some(Direction::Up));
+        assert_eq!(p.direction_to(&Point { x: 3, y: 3 }, 10, 5), None);
+        assert_eq!(p.direction_to(&Point { x: 9, y: 1 }, 10, 5), Some(Direction::UpLeft));
+        assert_eq!(p.direction_to(&Point { x: 1, y: 3 }, 10, 5), Some(Direction::DownRight));
+    }
+
+    #[test]
+    fn opposite_direction_is_ignored() {
+        let mut game = test_game(10, 5, false);
+
+        game.set_player_direction(0, Direction::Left);
+        assert_eq!(game.snake().next_direction, Direction::Right);
+
+        game.set_player_direction(0, Direction::Up);
+        assert_eq!(game.snake().next_direction, Direction::Up);
+    }
+
+    #[test]
+    fn quick_turns_are_queued_one_per_move() {
+        let mut game = test_game(20, 10, false);
+        game.food = vec![Point { x: 0, y: 0 }];
+        let head = game.snake().head();
+
+        // Up then left within one frame: a U-turn over the next two moves
+        game.set_player_direction(0, Direction::Up);
+        game.set_player_direction(0, Direction::Left);
+        // Reversing the last queued turn or repeating it is dropped, as is a turn past the cap
+        game.set_player_direction(0, Direction::Right);
+        game.set_player_direction(0, Direction::Left);
+        game.set_player_direction(0, Direction::Down);
+        game.set_player_direction(0, Direction::Right);
+
+        game.update();
+        assert_eq!(game.snake().head(), Point { x: head.x, y: head.y - 1 });
+        game.update();
+        assert_eq!(game.snake().head(), Point { x: head.x - 1, y: head.y - 1 });
+        game.update();
+        assert_eq!(game.snake().head(), Point { x: head.x - 1, y: head.y });
+        game.update();
+        assert_eq!(game.snake().head(), Point { x: head.x - 1, y: head.y + 1 });
+        assert_eq!(game.snake().direction, Direction::Down);
+    }
+
+    fn versus_game(width: i32, height: i32) -> GameState {
+        let settings = GameSettings {
+            versus: true,
+            ..GameSettings::new(width, height, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.waiting_for_start = false;
+        game.food = vec![Point { x: 0, y: 0 }];
+        game
+    }
+
+    #[test]
+    fn versus_snake_hitting_other_snake_loses() {
+        let mut game = versus_game(10, 6);
+        game.snakes[0].set_body([(2, 1), (1, 1), (0, 1)].iter().map(|&(x, y)| Point { x, y }));
+        game.snakes[1].set_body([(3, 3), (3, 2), (3, 1)].iter().map(|&(x, y)| Point { x, y }));
+        game.snakes[1].direction = Direction::Down;
+        game.snakes[1].next_direction = Direction::Down;
+
+        game.update();
+
+        assert!(!game.snakes[0].alive);
+        assert_eq!(game.status, GameStatus::GameOver);
+        assert_eq!(game.winner, Some(1));
+    }
+
+    #[test]
+    fn versus_head_on_collision_is_a_draw() {
+        let mut game = versus_game(10, 6);
+        game.snakes[0].set_body([(3, 2), (2, 2), (1, 2)].iter().map(|&(x, y)| Point { x, y }));
+        game.snakes[1].set_body([(5, 2), (6, 2), (7, 2)].iter().map(|&(x, y)| Point { x, y }));
+
+        game.update();
+
+        assert_eq!(game.status, GameStatus::GameOver);
+        assert_eq!(game.winner, None);
+    }
+
+    #[test]
+    fn versus_food_grows_only_the_eater() {
+        let mut game = versus_game(10, 6);
+        game.food = vec![Point { x: game.snakes[1].head().x - 1, y: game.snakes[1].head().y }];
+
+        game.update();
+
+        assert_eq!(game.snakes[0].body.len(), 3);
+        assert_eq!(game.snakes[1].body.len(), 4);
+        assert_eq!(game.status, GameStatus::Playing);
+    }
+
+    #[test]
+    fn countdown_holds_the_snake_before_the_first_move() {
+        let mut game = GameState::new(GameSettings::new(10, 5, 250), 0, 0).unwrap();
+        game.food = vec![Point { x: 0, y: 0 }];
+        let start = game.snake().head();
+
+        game.start_countdown();
+        game.set_player_direction(0, Direction::Down);
+        assert_eq!(game.countdown, Some(COUNTDOWN_START));
+
+        // 3, 2, 1, GO! each last a second of real time, whatever the speed
+        game.adjust_speed(true);
+        game.slowmo = true;
+        for remaining in (0..=COUNTDOWN_START).rev() {
+            game.update();
+            assert_eq!(game.countdown, Some(remaining));
+            assert!(!game.will_move());
+            game.countdown_tick -= COUNTDOWN_STEP;
+            game.update();
+        }
+        assert_eq!(game.countdown, None);
+        assert_eq!(game.snake().head(), start);
+
+        game.update();
+        assert_eq!(game.snake().head(), Point { x: start.x, y: start.y + 1 });
+    }
+
+    #[test]
+    fn eating_flashes_for_a_couple_of_moves() {
+        let mut game = test_game(10, 5, false);
+        place_snake(&mut game, &[(2, 2), (1, 2), (0, 2)]);
+        game.food = vec![Point { x: 3, y: 2 }];
+
+        game.update();
+        assert_eq!(game.eat_flash_frames, EAT_FLASH_FRAMES);
+
+        game.food = vec![Point { x: 0, y: 0 }];
+        game.bonus = None;
+        game.poison = None;
+        for _ in 0..EAT_FLASH_FRAMES {
+            game.update();
+        }
+        assert_eq!(game.eat_flash_frames, 0);
+    }
+
+    #[test]
+    fn quick_food_builds_a_combo_that_runs_out() {
+        let settings = GameSettings {
+            combos: true,
+            ..GameSettings::new(50, 5, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.waiting_for_start = false;
+        place_snake(&mut game, &[(2, 2), (1, 2), (0, 2)]);
+        game.bonus = None;
+        game.poison = None;
+
+        // Food on every move: 10, 15, 20, ... up to the cap
+        let mut scores = Vec::new();
+        for x in 3..10 {
+            game.food = vec![Point { x, y: 2 }];
+            game.update();
+            scores.push(game.score);
+        }
+        assert_eq!(scores, vec![10, 25, 45, 70, 100, 130, 160]);
+        assert_eq!(game.combo_multiplier(), 3.0);
+
+        // Waiting too long drops back to plain food
+        game.food = vec![Point { x: 0, y: 0 }];
+        for _ in 0..=COMBO_WINDOW_FRAMES {
+            game.update();
+        }
+        assert_eq!(game.status, GameStatus::Playing);
+        assert_eq!(game.combo_level, 0);
+        assert_eq!(game.combo_multiplier(), 1.0);
+    }
+
+    #[test]
+    fn time_attack_ends_the_game_when_the_clock_runs_out() {
+        let settings = GameSettings {
+            time_limit: Some(Duration::from_millis(500)),
+            ..GameSettings::new(30, 5, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.waiting_for_start = false;
+        place_snake(&mut game, &[(2, 2), (1, 2), (0, 2)]);
+        game.food = vec![Point { x: 0, y: 0 }];
+
+        // The clock runs on real play time (faked here), however many moves that was
+        game.update();
+        game.start_time -= Duration::from_millis(400);
+        let left = game.time_left().unwrap();
+        assert!(left <= Duration::from_millis(100) && left > Duration::from_millis(50));
+
+        // Time spent paused doesn't come off the clock
+        game.toggle_pause();
+        game.start_time -= Duration::from_secs(5);
+        game.pause_started = game.pause_started.map(|started| started - Duration::from_secs(5));
+        game.update();
+        game.toggle_pause();
+        assert!(game.time_left().unwrap() > Duration::from_millis(50));
+        game.update();
+        assert_eq!(game.status, GameStatus::Playing);
+        assert!(!game.time_up());
+
+        game.start_time -= Duration::from_millis(100);
+        game.update();
+        assert_eq!(game.status, GameStatus::GameOver);
+        assert!(game.time_up());
+        assert_eq!(game.time_left(), Some(Duration::ZERO));
+        assert_eq!(test_game(30, 5, false).time_left(), None);
+    }
+
+    #[test]
+    fn replayed_time_attack_ends_at_the_recorded_move() {
+        let settings = GameSettings {
+            time_limit: Some(Duration::from_millis(500)),
+            ..GameSettings::new(30, 5, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.waiting_for_start = false;
+        place_snake(&mut game, &[(2, 2), (1, 2), (0, 2)]);
+        game.food = vec![Point { x: 0, y: 0 }];
+
+        // With the move given, real time no longer matters
+        game.time_up_frame = Some(5);
+        game.start_time -= Duration::from_secs(10);
+        for _ in 0..4 {
+            game.update();
+        }
+        assert_eq!(game.status, GameStatus::Playing);
+        game.update();
+        assert!(game.time_up());
+        assert_eq!(game.frame, 5);
+    }
+
+    #[test]
+    fn valued_food_scores_its_own_value() {
+        let settings = GameSettings {
+            valued_food: true,
+            ..GameSettings::new(20, 5, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        assert!(game.food.iter().all(|food| FOOD_VALUE_RANGE.contains(&game.food_values[food])));
+
+        game.waiting_for_start = false;
+        place_snake(&mut game, &[(2, 2), (1, 2), (0, 2)]);
+        game.bonus = None;
+        game.poison = None;
+        let food = Point { x: 3, y: 2 };
+        game.food = vec![food];
+        game.food_values = HashMap::from([(food, 7)]);
+        game.update();
+        assert_eq!(game.score, 7);
+        assert!(!game.food_values.contains_key(&food));
+        // The food that replaced it has a value too
+        assert_eq!(game.food_values.len(), game.food.len());
+    }
+
+    #[test]
+    fn reverse_powerup_inverts_controls_for_a_while() {
+        let settings = GameSettings {
+            chaos: true,
+            ..GameSettings::new(60, 5, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.waiting_for_start = false;
+        place_snake(&mut game, &[(2, 2), (1, 2), (0, 2)]);
+        game.food = vec![Point { x: 0, y: 0 }];
+        game.bonus = None;
+        game.poison = None;
+        game.reverser = Some((Point { x: 3, y: 2 }, REVERSE_LIFETIME_FRAMES));
+
+        assert!(!game.controls_inverted());
+        game.update();
+        assert_eq!(game.reverser, None);
+        assert!(game.controls_inverted());
+        assert_eq!(game.score, 0);
+
+        // Direction keys given before each of the next REVERSE_EFFECT_FRAMES moves are reversed
+        for _ in 1..REVERSE_EFFECT_FRAMES {
+            game.update();
+        }
+        assert!(game.controls_inverted());
+        game.update();
+        assert_eq!(game.status, GameStatus::Playing);
+        assert!(!game.controls_inverted());
+        assert_eq!(Direction::Up.opposite(), Direction::Down);
+        assert_eq!(Direction::UpLeft.opposite(), Direction::DownRight);
+    }
+
+    #[test]
+    fn trail_mode_leaves_deadly_hazards() {
+        let settings = GameSettings {
+            hazard_trail: true,
+            ..GameSettings::new(10, 5, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.waiting_for_start = false;
+        place_snake(&mut game, &[(2, 2), (1, 2), (0, 2)]);
+        game.food = vec![Point { x: 3, y: 2 }];
+
+        game.update();
+        assert!(game.ate_food);
+        assert_eq!(game.hazards, vec![Point { x: 3, y: 2 }]);
+        assert!(!game.food.contains(&Point { x: 3, y: 2 }));
+
+        // Loop back around onto the old food spot
+        place_snake(&mut game, &[(3, 1), (4, 1), (5, 1)]);
+        game.snakes[0].direction = Direction::Down;
+        game.snakes[0].next_direction = Direction::Down;
+        game.food = vec![Point { x: 9, y: 4 }];
+        game.update();
+        assert_eq!(game.status, GameStatus::GameOver);
+    }
+
+    #[test]
+    fn portal_moves_head_past_its_partner() {
+        let settings = GameSettings {
+            portals: true,
+            ..GameSettings::new(12, 8, 100)
+        };
+        let mut game = GameState::new_seeded(settings, 0, 0, 7).unwrap();
+        game.waiting_for_start = false;
+        assert!(game.portals.is_some());
+
+        game.portals = Some((Point { x: 3, y: 2 }, Point { x: 8, y: 5 }));
+        place_snake(&mut game, &[(2, 2), (1, 2), (0, 2)]);
+        game.food = vec![Point { x: 0, y: 7 }];
+
+        game.update();
+
+        assert_eq!(game.snake().head(), Point { x: 9, y: 5 });
+        assert_eq!(game.status, GameStatus::Playing);
+    }
+
+    #[test]
+    fn portals_are_left_out_on_tiny_fields() {
+        let settings = GameSettings {
+            portals: true,
+            ..GameSettings::new(8, 5, 100)
+        };
+        assert_eq!(GameState::new(settings, 0, 0).unwrap().portals, None);
+    }
+
+    #[test]
+    fn diagonal_moves_only_in_diagonal_mode() {
+        let mut game = test_game(10, 5, false);
+        game.set_player_direction(0, Direction::UpRight);
+        assert_eq!(game.snake().next_direction, Direction::Right);
+
+        let settings = GameSettings {
+            diagonal: true,
+            ..GameSettings::new(10, 5, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.waiting_for_start = false;
+        place_snake(&mut game, &[(2, 2), (1, 2), (0, 2)]);
+        game.food = vec![Point { x: 9, y: 4 }];
+
+        game.set_player_direction(0, Direction::UpRight);
+        game.update();
+        assert_eq!(game.snake().head(), Point { x: 3, y: 1 });
+
+        // Straight back down-left is a reversal
+        game.set_player_direction(0, Direction::DownLeft);
+        assert_eq!(game.snake().next_direction, Direction::UpRight);
+    }
+
+    #[test]
+    fn play_clock_stands_still_while_paused() {
+        let mut game = GameState::new(GameSettings::new(10, 5, 100), 0, 0).unwrap();
+        assert_eq!(game.elapsed(), Duration::ZERO); // Not started yet
+
+        game.waiting_for_start = false;
+        game.toggle_pause();
+        let paused_at = game.elapsed();
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(game.elapsed(), paused_at);
+
+        game.toggle_pause();
+        assert!(game.elapsed() < paused_at + Duration::from_millis(20));
+    }
+
+    #[test]
+    fn shrinking_arena_closes_in() {
+        let settings = GameSettings {
+            shrinking: true,
+            ..GameSettings::new(12, 8, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.waiting_for_start = false;
+        place_snake(&mut game, &[(5, 4), (4, 4), (3, 4)]);
+        game.food = vec![Point { x: 0, y: 0 }];
+
+        game.shrink_arena();
+        assert_eq!(game.inset, 1);
+        assert_eq!(game.arena_size(), (10, 6));
+        assert!(game.food.iter().all(|food| game.in_arena(food)));
+        assert!(game.snake().alive);
+
+        // A snake hugging the old wall is caught outside the new one
+        place_snake(&mut game, &[(3, 1), (2, 1), (1, 1)]);
+        game.shrink_arena();
+        assert!(!game.snake().alive);
+
+        // Never smaller than the minimum
+        game.shrink_arena();
+        assert_eq!(game.inset, 2);
+    }
+
+    #[test]
+    fn rewind_goes_back_to_before_the_crash() {
+        let mut game = test_game(10, 5, false);
+        game.rewind_limit = 3;
+        game.high_score = 7;
+        place_snake(&mut game, &[(5, 2), (4, 2), (3, 2)]);
+        game.food = vec![Point { x: 0, y: 0 }];
+
+        for _ in 0..5 {
+            game.update(); // Four moves to the right edge, the fifth into the wall
+        }
+        assert_eq!(game.status, GameStatus::GameOver);
+
+        // Only the last three moves are kept; going back two lands before the fourth move
+        game.high_score = 9;
+        assert!(game.rewind(2));
+        assert_eq!(game.status, GameStatus::Paused);
+        assert_eq!(game.frame, 3);
+        assert_eq!(game.snake().head(), Point { x: 8, y: 2 });
+        assert_eq!(game.high_score, 9);
+
+        // One snapshot is left, then nothing
+        assert!(game.rewind(5));
+        assert_eq!(game.snake().head(), Point { x: 7, y: 2 });
+        assert!(!game.rewind(1));
+    }
+
+    #[test]
+    fn idle_player_is_auto_paused() {
+        let mut game = test_game(20, 10, false);
+        game.start_countdown();
+        game.countdown = None;
+        game.auto_pause_frames = 3;
+
+        for _ in 0..3 {
+            game.update();
+        }
+        assert_eq!(game.status, GameStatus::Playing);
+        assert_eq!(game.frame, 3);
+
+        game.update();
+        assert_eq!(game.status, GameStatus::Paused);
+        assert!(game.auto_paused);
+        assert_eq!(game.frame, 3); // The snake didn't move on the pausing frame
+
+        game.toggle_pause();
+        assert!(!game.auto_paused);
+
+        // Hardcore runs keep going however long the player is idle
+        game.settings.allow_pause = false;
+        for _ in 0..5 {
+            game.update();
+        }
+        assert_eq!(game.status, GameStatus::Playing);
+        assert_eq!(game.frame, 8);
+        assert_eq!(frames_for(Duration::from_secs(3), 100), 30);
+        assert_eq!(frames_for(Duration::ZERO, 100), 0);
+    }
+
+    #[test]
+    fn big_boards_keep_several_food_items() {
+        let mut game = test_game(60, 40, false);
+        assert_eq!(game.food.len(), 6);
+
+        let head = game.snake().head();
+        let eaten = Point { x: head.x + 1, y: head.y };
+        game.food[0] = eaten;
+        game.update();
+
+        assert_eq!(game.score, 10);
+        assert_eq!(game.food.len(), 6);
+        assert!(!game.food.contains(&eaten));
+        assert_eq!(test_game(20, 10, false).food.len(), 1);
+    }
+
+    #[test]
+    fn maps_set_up_walls_start_and_food_again_on_restart() {
+        let mut game = test_game(20, 10, false);
+        // A wall right of the start: down has room ahead and behind, left has none behind
+        let map = Map {
+            width: 20,
+            height: 10,
+            obstacles: vec![Point { x: 6, y: 4 }],
+            start: Point { x: 5, y: 4 },
+            food: vec![Point { x: 1, y: 1 }],
+        };
+        game.use_map(map.clone());
+
+        for _ in 0..2 {
+            assert_eq!(game.obstacles, map.obstacles);
+            assert_eq!(game.food, map.food);
+            assert_eq!(game.snake().direction, Direction::Down);
+            let body: Vec<Point> = game.snake().body().iter().copied().collect();
+            assert_eq!(body, vec![Point { x: 5, y: 4 }, Point { x: 5, y: 3 }, Point { x: 5, y: 2 }]);
+            game.restart();
+        }
+    }
+
+    #[test]
+    fn fair_spawn_puts_food_farther_from_the_head() {
+        let food_distance = |fair_spawn, seed| {
+            let settings = GameSettings {
+                fair_spawn,
+                ..GameSettings::new(30, 20, 100)
+            };
+            let game = GameState::new_seeded(settings, 0, 0, seed).unwrap();
+            let (head, food) = (game.snake().head(), game.food[0]);
+            (head.x - food.x).abs() + (head.y - food.y).abs()
+        };
+
+        // The plain pick is always one of the fair spawn's candidates
+        let mut total = (0, 0);
+        for seed in 0..50 {
+            let (plain, fair) = (food_distance(false, seed), food_distance(true, seed));
+            assert!(fair >= plain);
+            total = (total.0 + plain, total.1 + fair);
+        }
+        assert!(total.1 > total.0);
+    }
+
+    #[test]
+    fn slowmo_drains_and_recharges() {
+        let mut game = test_game(20, 10, true);
+        game.toggle_slowmo();
+        assert!(game.slowmo);
+        assert_eq!(game.step_duration(), Duration::from_millis(200));
+
+        for _ in 0..SLOWMO_MAX_BUDGET {
+            game.update();
+        }
+        assert_eq!(game.slowmo_budget, 0);
+        assert!(!game.slowmo); // Ran out and switched itself off
+        game.toggle_slowmo();
+        assert!(!game.slowmo);
+
+        for _ in 0..SLOWMO_RECHARGE_MOVES {
+            game.update();
+        }
+        assert_eq!(game.slowmo_budget, 1);
+        assert_eq!(game.step_duration(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn degenerate_boards_are_rejected() {
+        assert!(GameState::new(GameSettings::new(2, 2, 100), 0, 0).is_err());
+        assert!(GameState::new(GameSettings::new(5, 2, 100), 0, 0).is_err());
+        assert!(GameState::new(GameSettings::new(5, 3, 100), 0, 0).is_ok());
+
+        let versus = GameSettings {
+            versus: true,
+            ..GameSettings::new(7, 3, 100)
+        };
+        assert!(GameState::new(versus, 0, 0).is_err());
+        assert!(GameState::new(GameSettings { width: 8, ..versus }, 0, 0).is_ok());
+    }
+
+    #[test]
+    fn food_can_grow_the_snake_by_several_segments() {
+        let settings = GameSettings {
+            growth_per_food: 3,
+            ..GameSettings::new(20, 5, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.waiting_for_start = false;
+        place_snake(&mut game, &[(5, 2), (4, 2), (3, 2)]);
+        game.food = vec![Point { x: 6, y: 2 }];
+
+        game.update();
+        assert_eq!(game.snake().body.len(), 4);
+        game.food.clear(); // Food spawned right ahead would keep it growing
+        game.update();
+        game.update();
+        assert_eq!(game.snake().body.len(), 6);
+        game.update();
+        assert_eq!(game.snake().body.len(), 6); // Done growing
+    }
+
+    #[test]
+    fn length_cap_stops_growth_but_food_still_scores() {
+        let settings = GameSettings {
+            growth_per_food: 3,
+            max_length: 5,
+            ..GameSettings::new(20, 5, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.waiting_for_start = false;
+        place_snake(&mut game, &[(5, 2), (4, 2), (3, 2)]);
+        game.food = vec![Point { x: 6, y: 2 }];
+
+        game.update();
+        game.food = vec![Point { x: 8, y: 2 }];
+        game.bonus = None;
+        game.poison = None;
+        game.update();
+        assert_eq!(game.snake().body.len(), 5);
+        assert!(game.at_max_length(0));
+
+        // At the cap the next food scores, and the tail moves along as the head advances
+        game.update();
+        assert_eq!(game.score, 20);
+        assert_eq!(game.snake().body.len(), 5);
+        game.food.clear();
+        game.update();
+        assert_eq!(game.snake().body.len(), 5);
+    }
+
+    #[test]
+    fn head_may_follow_the_tail_when_not_growing() {
+        let mut game = test_game(10, 5, false);
+        // Square loop: moving down puts the head on the tail's cell just as the tail leaves it
+        place_snake(&mut game, &[(1, 1), (2, 1), (2, 2), (1, 2)]);
+        game.snakes[0].direction = Direction::Left;
+        game.snakes[0].next_direction = Direction::Left;
+        game.set_player_direction(0, Direction::Down);
+        game.food = vec![Point { x: 8, y: 4 }];
+
+        game.update();
+
+        assert_eq!(game.status, GameStatus::Playing);
+        assert_eq!(game.snake().head(), Point { x: 1, y: 2 });
+        assert_eq!(game.snake().body.len(), 4);
+    }
+
+    #[test]
+    fn head_hits_the_tail_while_growing() {
+        let mut game = test_game(10, 5, false);
+        place_snake(&mut game, &[(1, 1), (2, 1), (2, 2), (1, 2)]);
+        game.snakes[0].direction = Direction::Left;
+        game.snakes[0].pending_growth = 1; // Still digesting: the tail stays where it is
+        game.set_player_direction(0, Direction::Down);
+        game.food = vec![Point { x: 8, y: 4 }];
+
+        game.update();
+
+        assert_eq!(game.status, GameStatus::GameOver);
+    }
+
+    #[test]
+    fn campaign_advances_levels_and_ends_in_a_win() {
+        let settings = GameSettings {
+            layout: Layout::Campaign,
+            ..GameSettings::new(30, 20, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.waiting_for_start = false;
+        game.food = vec![Point { x: 16, y: 10 }];
+        game.score = LEVELS[0].goal - 10;
+
+        game.update();
+        assert_eq!(game.level, 1);
+        assert_eq!(game.obstacles, LEVELS[1].obstacles(30, 20));
+        assert_eq!(game.snake().body.len(), 3); // Back at the start
+        assert_eq!(game.countdown, Some(COUNTDOWN_START));
+
+        game.level = LEVELS.len() - 1;
+        game.countdown = None;
+        game.score = LEVELS[game.level].goal;
+        game.update();
+        assert_eq!(game.status, GameStatus::Won);
+    }
+
+    #[test]
+    fn speed_keys_adjust_the_delay_within_bounds() {
+        let mut game = test_game(20, 10, true);
+        game.adjust_speed(true);
+        assert_eq!(game.frame_duration, Duration::from_millis(90));
+
+        // The adjustment survives the score-based speed-up
+        game.score = SPEED_UP_SCORE_INTERVAL;
+        game.update_speed();
+        assert_eq!(game.frame_duration, Duration::from_millis(85));
+
+        for _ in 0..20 {
+            game.adjust_speed(true);
+        }
+        assert_eq!(game.frame_duration, Duration::from_millis(DEFAULT_MIN_FRAME_MS));
+        game.adjust_speed(false);
+        assert_eq!(game.frame_duration, Duration::from_millis(DEFAULT_MIN_FRAME_MS + 10));
+
+        game.restart();
+        assert_eq!(game.frame_duration, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn speed_changes_stay_within_the_frame_bounds() {
+        let settings = GameSettings {
+            min_frame_ms: 80,
+            max_frame_ms: 120,
+            ..GameSettings::new(20, 10, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        for _ in 0..10 {
+            game.adjust_speed(false);
+        }
+        assert_eq!(game.frame_duration, Duration::from_millis(120));
+        game.adjust_speed(true);
+        assert_eq!(game.frame_duration, Duration::from_millis(110));
+
+        game.score = 50 * SPEED_UP_SCORE_INTERVAL;
+        for _ in 0..10 {
+            game.adjust_speed(true);
+        }
+        assert_eq!(game.frame_duration, Duration::from_millis(80));
+
+        // A starting delay outside the bounds is kept, not pulled back in
+        let slow = GameSettings { delay_ms: 300, ..settings };
+        let mut game = GameState::new(slow, 0, 0).unwrap();
+        game.update_speed();
+        assert_eq!(game.frame_duration, Duration::from_millis(300));
+    }
+
+    #[test]
+    fn length_curve_speeds_up_as_the_snake_grows() {
+        let settings = GameSettings {
+            speed_curve: SpeedCurve::Length,
+            ..GameSettings::new(20, 10, 100)
+        };
+        let mut game = GameState::new(settings, 0, 0).unwrap();
+        game.update_speed();
+        assert_eq!(game.frame_duration, Duration::from_millis(100));
+
+        // Score alone changes nothing
+        game.score = 500;
+        game.update_speed();
+        assert_eq!(game.frame_duration, Duration::from_millis(100));
+
+        // 23 of the 47 segments to a quarter of the 200-cell board: 23/47 of the way to the floor
+        let cells = |length: i32| (0..length).map(|i| Point { x: i % 20, y: i / 20 });
+        game.snakes[0].set_body(cells(26));
+        game.update_speed();
+        assert_eq!(game.frame_duration, Duration::from_millis(100 - 60 * 23 / 47));
+
+        game.snakes[0].set_body(cells(120));
+        game.update_speed();
+        assert_eq!(game.frame_duration, Duration::from_millis(DEFAULT_MIN_FRAME_MS));
+    }
+}
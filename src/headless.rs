@@ -0,0 +1,75 @@
+//! Running games without a screen (--headless), for benchmarking the autopilot
+//!
+//! Updates run back to back with no sleeping and no curses, so thousands of
+//! games can be played in the time one takes on screen.
+
+use rust_snake::replay::Recording;
+use rust_snake::game::frames_for;
+use rust_snake::{autopilot, GameState};
+
+/// Safety net for an autopilot that circles forever without dying or eating
+const MAX_FRAMES: u64 = 1_000_000;
+
+/// Play a game to the end, steered by the autopilot or by a recording's moves
+/// The game stops early after MAX_FRAMES moves
+pub fn run(mut game: GameState, replay: Option<&Recording>) -> GameState {
+    // Nobody is there to press a key, so skip the wait and the countdown
+    game.waiting_for_start = false;
+    // No real time passes either, so a time attack ends at the recorded move (never, if the
+    // recorded game ended otherwise), or after the moves the limit holds at the starting speed
+    if let Some(limit) = game.settings.time_limit {
+        game.time_up_frame = Some(match replay {
+            Some(recording) => recording.time_up.unwrap_or(u64::MAX),
+            None => frames_for(limit, game.settings.delay_ms) as u64,
+        });
+    }
+
+    while !game.is_finished() && game.frame < MAX_FRAMES {
+        match replay {
+            Some(recording) => {
+                for (player, direction) in recording.directions_at(game.frame) {
+                    game.set_player_direction(player, direction);
+                }
+                for _ in 0..recording.rerolls_at(game.frame) {
+                    game.reroll_food();
+                }
+            }
+            None => {
+                if let Some(direction) = autopilot::choose_direction(&game) {
+                    game.set_player_direction(0, direction);
+                }
+            }
+        }
+        game.update();
+    }
+    game
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_snake::GameSettings;
+
+    #[test]
+    fn autopilot_game_runs_to_the_end() {
+        let settings = GameSettings::new(10, 5, 100);
+        let game = GameState::new_seeded(settings, 0, 0, 3).unwrap();
+        let game = run(game, None);
+
+        assert!(game.is_finished() || game.frame == MAX_FRAMES);
+        assert!(game.score > 0);
+    }
+
+    #[test]
+    fn replays_the_recorded_moves() {
+        let settings = GameSettings::new(10, 5, 100);
+        let mut recording = Recording::new(5, settings);
+        recording.moves.push((2, 0, rust_snake::Direction::Up));
+
+        let game = run(GameState::new_seeded(settings, 0, 0, 5).unwrap(), Some(&recording));
+
+        // Heading up from the middle row hits the top wall a few moves later
+        assert!(game.is_finished());
+        assert!(game.frame <= 5);
+    }
+}
@@ -0,0 +1,62 @@
+//! Persistent high scores, one "mode score" line per game mode, in the user's home directory
+//!
+//! A file holding just a number (from before scores were kept per mode) is the classic record.
+
+use crate::config::home_path;
+use rust_snake::game::CLASSIC_MODE;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const FILE_NAME: &str = ".rust-snake-highscore";
+
+/// Location of the high score file
+fn path() -> PathBuf {
+    home_path(FILE_NAME)
+}
+
+/// Every mode's record; garbled lines are skipped
+fn parse(contents: &str) -> BTreeMap<String, u32> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (mode, score) = line.trim().split_once(' ').unwrap_or((CLASSIC_MODE, line.trim()));
+            Some((mode.to_string(), score.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+fn to_text(records: &BTreeMap<String, u32>) -> String {
+    records.iter().map(|(mode, score)| format!("{} {}\n", mode, score)).collect()
+}
+
+fn load_all() -> BTreeMap<String, u32> {
+    fs::read_to_string(path()).map(|contents| parse(&contents)).unwrap_or_default()
+}
+
+/// Read the stored high score for a mode; a missing or garbled record counts as 0
+pub fn load(mode: &str) -> u32 {
+    load_all().get(mode).copied().unwrap_or(0)
+}
+
+/// Overwrite the stored high score for a mode, keeping the other modes' records
+pub fn save(mode: &str, score: u32) -> io::Result<()> {
+    let mut records = load_all();
+    records.insert(mode.to_string(), score);
+    fs::write(path(), to_text(&records))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_a_record_per_mode_and_reads_old_files() {
+        let records = parse("120\nwrap+tron 45\nblocks nonsense\n");
+        assert_eq!(records.get(CLASSIC_MODE), Some(&120));
+        assert_eq!(records.get("wrap+tron"), Some(&45));
+        assert_eq!(records.len(), 2);
+        assert_eq!(parse(&to_text(&records)), records);
+    }
+}
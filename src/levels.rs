@@ -0,0 +1,108 @@
+//! Built-in campaign levels, played in order with the Campaign obstacle layout
+//!
+//! Walls are given in percent of the board (0-100 on each axis) so every level
+//! scales to whatever field size was picked. Reaching a level's goal score moves
+//! on to the next one; finishing the last level wins the game.
+
+use crate::game::Point;
+
+/// A straight horizontal or vertical wall, from one corner to the other (inclusive)
+#[derive(Debug, Clone, Copy)]
+pub struct Wall {
+    pub from: (i32, i32), // Percent of width and height
+    pub to: (i32, i32),
+}
+
+/// One stage of the campaign
+#[derive(Debug)]
+pub struct Level {
+    pub name: &'static str,
+    pub goal: u32, // Total score that completes the level
+    pub walls: &'static [Wall],
+}
+
+impl Level {
+    /// Obstacle cells for a board of the given size
+    pub fn obstacles(&self, width: i32, height: i32) -> Vec<Point> {
+        let to_cell = |(px, py): (i32, i32)| (px * (width - 1) / 100, py * (height - 1) / 100);
+
+        let mut obstacles = Vec::new();
+        for wall in self.walls {
+            let (x1, y1) = to_cell(wall.from);
+            let (x2, y2) = to_cell(wall.to);
+            for x in x1.min(x2)..=x1.max(x2) {
+                for y in y1.min(y2)..=y1.max(y2) {
+                    let cell = Point { x, y };
+                    if !obstacles.contains(&cell) {
+                        obstacles.push(cell);
+                    }
+                }
+            }
+        }
+        obstacles
+    }
+}
+
+pub const LEVELS: [Level; 4] = [
+    Level {
+        name: "Open Field",
+        goal: 50,
+        walls: &[],
+    },
+    Level {
+        name: "Twin Bars",
+        goal: 120,
+        walls: &[
+            Wall { from: (20, 15), to: (80, 15) },
+            Wall { from: (20, 85), to: (80, 85) },
+        ],
+    },
+    Level {
+        name: "Pillars",
+        goal: 200,
+        walls: &[
+            Wall { from: (10, 20), to: (10, 80) },
+            Wall { from: (90, 20), to: (90, 80) },
+            Wall { from: (35, 0), to: (35, 15) },
+            Wall { from: (65, 85), to: (65, 100) },
+        ],
+    },
+    Level {
+        name: "Cross",
+        goal: 300,
+        walls: &[
+            Wall { from: (10, 50), to: (30, 50) },
+            Wall { from: (70, 50), to: (90, 50) },
+            Wall { from: (50, 10), to: (50, 30) },
+            Wall { from: (50, 70), to: (50, 90) },
+        ],
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameSettings, GameState, FIELD_SIZES};
+
+    #[test]
+    fn starting_snakes_are_clear_of_every_level() {
+        for size in FIELD_SIZES {
+            for versus in [false, true] {
+                let settings = GameSettings {
+                    versus,
+                    ..GameSettings::new(size.width, size.height, 100)
+                };
+                let game = GameState::new(settings, 0, 0).unwrap();
+                for level in &LEVELS {
+                    let obstacles = level.obstacles(size.width, size.height);
+                    let blocked = game
+                        .snakes
+                        .iter()
+                        .flat_map(|snake| snake.body())
+                        .any(|cell| obstacles.contains(cell));
+                    assert!(!blocked, "{} blocks a starting snake on {}", level.name, size.name);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,31 @@
+//! Snake game engine with no terminal dependency
+//!
+//! The curses game in `main.rs` is one frontend; any other renderer can drive
+//! the same engine. Each tick it feeds in directions, calls `update`, and
+//! reads back the snakes, food, and status:
+//!
+//! ```
+//! use rust_snake::{Direction, GameSettings, GameState, GameStatus, FIELD_SIZES};
+//!
+//! let size = FIELD_SIZES[0];
+//! let mut game = GameState::new(GameSettings::new(size.width, size.height, 100), 0, 0).unwrap();
+//!
+//! game.set_player_direction(0, Direction::Up);
+//! game.skip_start_wait(); // Or start_countdown() for a 3-second countdown before the first move
+//! while game.status == GameStatus::Playing && game.frame < 5 {
+//!     game.update(); // Wait game.step_duration() between calls
+//! }
+//!
+//! let head = game.snake().head();
+//! println!("head at {},{}; food at {:?}; score {}", head.x, head.y, game.food, game.score);
+//! ```
+
+pub mod autopilot;
+pub mod game;
+pub mod levels;
+pub mod map;
+pub mod replay;
+
+pub use game::{
+    Direction, FieldSize, GameSettings, GameState, GameStatus, Layout, Point, Snake, SpeedCurve, FIELD_SIZES,
+};
@@ -1,43 +1,107 @@
-use pancurses::{endwin, initscr, Input, Window};
-use rand::Rng;
+//! Curses frontend for the snake engine in the library crate
+
+mod cli;
+mod config;
+mod headless;
+mod highscore;
+mod pipe;
+mod scores;
+mod summary;
+
+use config::{Action, KeyBindings};
+use pancurses::{chtype, endwin, initscr, Input, Window};
+use rust_snake::game::{self, FieldSize, FIELD_SIZES};
+use rust_snake::levels;
+use rust_snake::map::Map;
+use rust_snake::replay::Recording;
+use rust_snake::{autopilot, Direction, GameSettings, GameState, GameStatus, Layout, Point, SpeedCurve};
 use std::collections::VecDeque;
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 // ============================================================================
 // GAME CONSTANTS
 // ============================================================================
 
-// Playing field size options
-#[derive(Debug, Clone, Copy)]
-struct FieldSize {
-    name: &'static str,
-    width: i32,
-    height: i32,
-}
+// Self-collisions forgiven per game with the "Forgiving" rules
+const FORGIVING_COLLISIONS: u32 = 3;
 
-const FIELD_SIZES: [FieldSize; 4] = [
-    FieldSize {
-        name: "Tiny",
-        width: 20,
-        height: 10,
-    },
-    FieldSize {
-        name: "Small",
-        width: 30,
-        height: 20,
-    },
-    FieldSize {
-        name: "Medium",
-        width: 40,
-        height: 30,
-    },
-    FieldSize {
-        name: "Large",
-        width: 60,
-        height: 40,
-    },
-];
+// Allowed dimensions for a custom field size
+const CUSTOM_WIDTH_RANGE: std::ops::RangeInclusive<i32> = 10..=200;
+const CUSTOM_HEIGHT_RANGE: std::ops::RangeInclusive<i32> = 5..=100;
+
+// Allowed movement delay (ms) for the custom difficulty and --speed
+const CUSTOM_DELAY_RANGE: std::ops::RangeInclusive<u64> = 10..=1000;
+
+// Percent chance of poison food after each food for the None / Rare / Frequent choices
+const POISON_CHANCES: [u32; 3] = [0, 10, 30];
+
+// Moves without food before losing a segment for the Off / Hungry / Starving choices
+const HUNGER_LIMITS: [u32; 3] = [0, 150, 75];
+const HUNGER_NAMES: [&str; 3] = ["Off", "Hungry", "Starving"];
+
+// Allowed starting snake length for --length, and length cap for --max-length
+const START_LENGTH_RANGE: std::ops::RangeInclusive<u32> = 1..=100;
+const MAX_LENGTH_RANGE: std::ops::RangeInclusive<u32> = 2..=10_000;
+
+// Seconds without a key press before the game pauses itself (--auto-pause overrides)
+const DEFAULT_AUTO_PAUSE_SECS: u64 = 3;
+const AUTO_PAUSE_RANGE: std::ops::RangeInclusive<u64> = 0..=600;
+
+// Visibility radius allowed for --fog (Chebyshev distance from a head, in cells)
+const FOG_RADIUS_RANGE: std::ops::RangeInclusive<i32> = 1..=50;
+
+// Time limits allowed for --time-attack, in seconds
+const TIME_ATTACK_RANGE: std::ops::RangeInclusive<u64> = 10..=3600;
+
+// How long a dead snake flashes and collapses before the game-over message (any key skips it)
+const DEATH_ANIMATION: Duration = Duration::from_millis(800);
+
+// --debug: the FPS readout averages over this many rendered frames
+const FPS_SAMPLE_FRAMES: usize = 30;
+
+// Ctrl+R as curses reports it; restarts straight away, even mid-game
+const CTRL_R: char = '\u{12}';
+
+// --scroll: smallest slice of the field (in cells) worth playing on
+const SCROLL_MIN_WIDTH: i32 = 10;
+const SCROLL_MIN_HEIGHT: i32 = 5;
+
+// Practice mode: moves remembered for rewinding, and how far back one press of the rewind key goes
+const REWIND_HISTORY: usize = 20;
+const REWIND_MOVES: usize = 5;
+
+// Most game steps run back to back to catch up after a slow frame
+const MAX_CATCH_UP_STEPS: u32 = 3;
+
+// Number of cells in the board fill progress bar of the info panel
+const FILL_BAR_WIDTH: usize = 10;
+
+// Rows the info panel takes above the field, and the columns its lines are cut off at
+// (so it fits next to an 80-column terminal's edge)
+const INFO_PANEL_HEIGHT: i32 = 4;
+const INFO_PANEL_WIDTH: i32 = 78;
+
+// Size menu attract mode: idle time before a self-playing demo starts under the menu,
+// the demo's board (in cells), and the time between its moves
+const ATTRACT_DELAY: Duration = Duration::from_secs(3);
+const ATTRACT_WIDTH: i32 = 24;
+const ATTRACT_HEIGHT: i32 = 8;
+const ATTRACT_TICK_MS: i32 = 90;
+
+// How often a menu waiting for a key looks whether Ctrl+C has been pressed
+const INTERRUPT_POLL_MS: i32 = 100;
+
+// Set by the Ctrl+C (or kill) handler; menus and the game loop see it and quit as usual,
+// so curses is only ever touched from the main thread and shut down by Renderer's drop
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether Ctrl+C has been pressed
+fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
 
 // Difficulty levels control snake movement speed
 #[derive(Debug, Clone, Copy)]
@@ -70,206 +134,412 @@ const DIFFICULTIES: [Difficulty; 4] = [
     },
 ];
 
-// ============================================================================
-// GAME STATE STRUCTURES
-// ============================================================================
+// The last difficulty asks for a delay instead of having one
+const CUSTOM_DIFFICULTY: usize = DIFFICULTIES.len() - 1;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Point {
-    x: i32,
-    y: i32,
-}
+// Growth per food on offer next to the difficulty, from +1 segment up
+const GROWTH_DESCRIPTIONS: [&str; 3] = ["classic", "double", "triple - the board fills up fast"];
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+/// What the difficulty screen settled on: the speed plus the options toggled beside it
+#[derive(Debug, Clone, Copy)]
+struct DifficultyChoice {
+    delay_ms: u64,
+    growth_per_food: u32,
+    allow_pause: bool, // Off for a hardcore run
+    assist: bool,
+    speed_curve: SpeedCurve,
+    hunger: usize, // Index into HUNGER_LIMITS
+    forgiving: bool, // The first FORGIVING_COLLISIONS self-collisions are forgiven
 }
 
-impl Direction {
-    /// Returns true if the other direction is opposite to this one
-    fn is_opposite(&self, other: &Direction) -> bool {
-        matches!(
-            (self, other),
-            (Direction::Up, Direction::Down)
-                | (Direction::Down, Direction::Up)
-                | (Direction::Left, Direction::Right)
-                | (Direction::Right, Direction::Left)
-        )
-    }
+/// What the wall mode screen settled on: the walls plus the board options toggled beside them
+#[derive(Debug, Clone, Copy)]
+struct BoardChoice {
+    wrap_walls: bool,
+    doorways: bool,
+    layout: Layout,
+    hazard_trail: bool,
+    portals: bool,
+    diagonal: bool,
+    shrinking: bool,
+    poison: usize, // Index into POISON_CHANCES
+    tron: bool,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum GameStatus {
-    Playing,
-    Paused,
-    GameOver,
-}
+// ============================================================================
+// RENDERING LAYER
+// ============================================================================
 
-struct GameState {
-    snake: VecDeque<Point>,
-    direction: Direction,
-    next_direction: Direction, // Buffered direction to prevent double-key issues
-    food: Point,
-    score: u32,
-    status: GameStatus,
-    game_width: i32,
-    game_height: i32,
-    offset_x: i32, // Offset for centering the game window
-    offset_y: i32, // Offset for centering the game window
-    waiting_for_start: bool, // Initial pause until first arrow key press
-    frame_delay: Duration, // Movement speed delay based on difficulty
-}
+// --afterimage: color pairs for the head's earlier cells, brightest first, set up in
+// `Renderer::new` on 256-color terminals (a grey ramp, so it suits every palette)
+const AFTERIMAGE_PAIRS: [chtype; game::HEAD_TRAIL_LENGTH] = [15, 16, 17, 18];
+const AFTERIMAGE_COLORS: [i16; game::HEAD_TRAIL_LENGTH] = [250, 245, 240, 236];
+
+// --fade: color pairs for the snake's body from head to tail, set up in `Renderer::new` on
+// 256-color terminals from the palette's fade_colors
+const FADE_PAIRS: [chtype; 5] = [19, 20, 21, 22, 23];
+
+// --rainbow: color pairs the snake cycles through, set up in `Renderer::new`
+const RAINBOW_PAIRS: [chtype; 6] = [7, 8, 9, 10, 11, 12];
+const RAINBOW_COLORS: [i16; 6] = [
+    pancurses::COLOR_RED,
+    pancurses::COLOR_YELLOW,
+    pancurses::COLOR_GREEN,
+    pancurses::COLOR_CYAN,
+    pancurses::COLOR_BLUE,
+    pancurses::COLOR_MAGENTA,
+];
 
-impl GameState {
-    fn new(width: i32, height: i32, offset_x: i32, offset_y: i32, delay_ms: u64) -> Self {
-        let mut snake = VecDeque::new();
-        // Start snake in the center
-        let center_x = width / 2;
-        let center_y = height / 2;
+/// Glyphs and color pairs for everything on the game screen, selected with --theme
+/// Color pairs refer to the ones set up in `Renderer::new`; the snake's own
+/// shape comes from `SnakeGlyphs`
+#[derive(Debug)]
+struct Theme {
+    name: &'static str,
+    border: char,
+    food: char,
+    bonus: char,
+    poison: char,
+    reverser: char, // Chaos mode's reverse-controls powerup
+    obstacle: char,
+    hazard: char,
+    tron_wall: char,
+    portal: char,
+    snake_pair: chtype,
+    player2_pair: chtype,
+    food_pair: chtype,
+    poison_pair: chtype,
+    border_pair: chtype,
+    text_pair: chtype,
+    obstacle_pair: chtype,
+    tron_pair: chtype,
+    portal_pair: chtype,
+    highlight_pair: chtype, // Bonus food, countdown, and waiting/paused/high score notices
+    alert_pair: chtype,     // Game over and error messages
+}
 
-        snake.push_back(Point {
-            x: center_x,
-            y: center_y,
-        });
-        snake.push_back(Point {
-            x: center_x - 1,
-            y: center_y,
-        });
-        snake.push_back(Point {
-            x: center_x - 2,
-            y: center_y,
-        });
+const THEMES: [Theme; 2] = [
+    Theme {
+        name: "classic",
+        border: '#',
+        food: '@',
+        bonus: '$',
+        poison: '!',
+        reverser: '?',
+        obstacle: 'X',
+        hazard: '%',
+        tron_wall: '=',
+        portal: 'O',
+        snake_pair: 1,
+        player2_pair: 6,
+        food_pair: 2,
+        poison_pair: 13,
+        border_pair: 3,
+        text_pair: 4,
+        obstacle_pair: 5,
+        tron_pair: 14,
+        portal_pair: 6,
+        highlight_pair: 3,
+        alert_pair: 2,
+    },
+    // Green-screen terminal look, with an amber second player
+    Theme {
+        name: "retro",
+        border: '+',
+        food: '*',
+        bonus: '&',
+        poison: '~',
+        reverser: '?',
+        obstacle: '=',
+        hazard: ':',
+        tron_wall: ';',
+        portal: '0',
+        snake_pair: 1,
+        player2_pair: 3,
+        food_pair: 1,
+        poison_pair: 1,
+        border_pair: 1,
+        text_pair: 1,
+        obstacle_pair: 1,
+        tron_pair: 1,
+        portal_pair: 1,
+        highlight_pair: 1,
+        alert_pair: 1,
+    },
+];
 
-        let mut game = GameState {
-            snake,
-            direction: Direction::Right,
-            next_direction: Direction::Right,
-            food: Point { x: 0, y: 0 }, // Will be set by spawn_food
-            score: 0,
-            status: GameStatus::Playing,
-            game_width: width,
-            game_height: height,
-            offset_x,
-            offset_y,
-            waiting_for_start: true, // Start paused until first arrow key
-            frame_delay: Duration::from_millis(delay_ms),
-        };
+/// Colors behind the numbered pairs the themes use, selected with --palette
+#[derive(Debug)]
+struct Palette {
+    name: &'static str,
+    pairs: [(i16, i16, i16); 8], // Pair number, foreground, background
+    fade_colors: [i16; FADE_PAIRS.len()], // Shades of the snake's color (256-color numbers), brightest first
+    distinct_shapes: bool,       // Player 2's snake gets its own glyphs, so color isn't the only cue
+}
 
-        game.spawn_food();
-        game
-    }
+const PALETTES: [Palette; 3] = [
+    Palette {
+        name: "standard",
+        pairs: [
+            (1, pancurses::COLOR_GREEN, pancurses::COLOR_BLACK),   // Snake
+            (2, pancurses::COLOR_RED, pancurses::COLOR_BLACK),     // Food
+            (3, pancurses::COLOR_YELLOW, pancurses::COLOR_BLACK),  // Border
+            (4, pancurses::COLOR_WHITE, pancurses::COLOR_BLACK),   // Text
+            (5, pancurses::COLOR_BLUE, pancurses::COLOR_BLACK),    // Obstacles
+            (6, pancurses::COLOR_CYAN, pancurses::COLOR_BLACK),    // Player 2 snake, portals
+            (13, pancurses::COLOR_MAGENTA, pancurses::COLOR_BLACK), // Poison food
+            (14, pancurses::COLOR_BLACK, pancurses::COLOR_CYAN),   // Tron walls
+        ],
+        fade_colors: [46, 40, 34, 28, 22], // Greens
+        distinct_shapes: false,
+    },
+    // Red-green (green-weak): blue snake against yellow food, no red or green at all
+    Palette {
+        name: "deuteranopia",
+        pairs: [
+            (1, pancurses::COLOR_CYAN, pancurses::COLOR_BLACK),
+            (2, pancurses::COLOR_YELLOW, pancurses::COLOR_BLACK),
+            (3, pancurses::COLOR_WHITE, pancurses::COLOR_BLACK),
+            (4, pancurses::COLOR_WHITE, pancurses::COLOR_BLACK),
+            (5, pancurses::COLOR_BLUE, pancurses::COLOR_BLACK),
+            (6, pancurses::COLOR_MAGENTA, pancurses::COLOR_BLACK),
+            (13, pancurses::COLOR_BLACK, pancurses::COLOR_MAGENTA),
+            (14, pancurses::COLOR_BLACK, pancurses::COLOR_WHITE),
+        ],
+        fade_colors: [51, 44, 37, 30, 23], // Cyans
+        distinct_shapes: true,
+    },
+    // Red-green (red-weak, where red looks dark): yellow snake against cyan food
+    Palette {
+        name: "protanopia",
+        pairs: [
+            (1, pancurses::COLOR_YELLOW, pancurses::COLOR_BLACK),
+            (2, pancurses::COLOR_CYAN, pancurses::COLOR_BLACK),
+            (3, pancurses::COLOR_WHITE, pancurses::COLOR_BLACK),
+            (4, pancurses::COLOR_WHITE, pancurses::COLOR_BLACK),
+            (5, pancurses::COLOR_BLUE, pancurses::COLOR_BLACK),
+            (6, pancurses::COLOR_MAGENTA, pancurses::COLOR_BLACK),
+            (13, pancurses::COLOR_BLACK, pancurses::COLOR_WHITE),
+            (14, pancurses::COLOR_BLACK, pancurses::COLOR_BLUE),
+        ],
+        fade_colors: [226, 220, 178, 136, 94], // Yellows into brown
+        distinct_shapes: true,
+    },
+];
 
-    /// Generate random food position that doesn't overlap with the snake
-    fn spawn_food(&mut self) {
-        let mut rng = rand::thread_rng();
-        loop {
-            let food = Point {
-                x: rng.gen_range(0..self.game_width),
-                y: rng.gen_range(0..self.game_height),
-            };
+/// Glyphs used to draw the snake, chosen by the direction(s) each segment connects to
+struct SnakeGlyphs {
+    head_up: &'static str,
+    head_down: &'static str,
+    head_left: &'static str,
+    head_right: &'static str,
+    horizontal: &'static str,
+    vertical: &'static str,
+    corner_down_right: &'static str,
+    corner_down_left: &'static str,
+    corner_up_right: &'static str,
+    corner_up_left: &'static str,
+    head_diagonal: &'static str, // Any diagonal heading (diagonal mode)
+    falling: &'static str,       // Diagonal piece from top-left to bottom-right
+    rising: &'static str,        // Diagonal piece from bottom-left to top-right
+}
 
-            // Check if food spawned on snake
-            if !self.snake.iter().any(|segment| *segment == food) {
-                self.food = food;
-                break;
-            }
+// Plain-ASCII fallback that works in any terminal
+const ASCII_SNAKE: SnakeGlyphs = SnakeGlyphs {
+    head_up: "^",
+    head_down: "v",
+    head_left: "<",
+    head_right: ">",
+    horizontal: "-",
+    vertical: "|",
+    corner_down_right: "+",
+    corner_down_left: "+",
+    corner_up_right: "+",
+    corner_up_left: "+",
+    head_diagonal: "O",
+    falling: "\\",
+    rising: "/",
+};
+
+// Player 2's ASCII body when the palette asks for distinct shapes: a string of beads
+const ASCII_SNAKE_ALT: SnakeGlyphs = SnakeGlyphs {
+    horizontal: "o",
+    vertical: "o",
+    corner_down_right: "o",
+    corner_down_left: "o",
+    corner_up_right: "o",
+    corner_up_left: "o",
+    falling: "o",
+    rising: "o",
+    ..ASCII_SNAKE
+};
+
+// Box-drawing set for UTF-8 terminals
+const UNICODE_SNAKE: SnakeGlyphs = SnakeGlyphs {
+    head_up: "▲",
+    head_down: "▼",
+    head_left: "◀",
+    head_right: "▶",
+    horizontal: "━",
+    vertical: "┃",
+    corner_down_right: "┏",
+    corner_down_left: "┓",
+    corner_up_right: "┗",
+    corner_up_left: "┛",
+    head_diagonal: "◆",
+    falling: "╲",
+    rising: "╱",
+};
+
+// Player 2's UTF-8 body when the palette asks for distinct shapes: double lines
+const UNICODE_SNAKE_ALT: SnakeGlyphs = SnakeGlyphs {
+    horizontal: "═",
+    vertical: "║",
+    corner_down_right: "╔",
+    corner_down_left: "╗",
+    corner_up_right: "╚",
+    corner_up_left: "╝",
+    ..UNICODE_SNAKE
+};
+
+impl SnakeGlyphs {
+    fn head(&self, direction: Direction) -> &'static str {
+        match direction {
+            Direction::Up => self.head_up,
+            Direction::Down => self.head_down,
+            Direction::Left => self.head_left,
+            Direction::Right => self.head_right,
+            _ => self.head_diagonal,
         }
     }
 
-    /// Update the direction if the new direction is valid (not opposite)
-    fn set_direction(&mut self, new_direction: Direction) {
-        if !self.direction.is_opposite(&new_direction) {
-            self.next_direction = new_direction;
+    /// Piece joining a segment to its neighbours in directions `a` and `b`
+    fn body(&self, a: Direction, b: Direction) -> &'static str {
+        use Direction::*;
+        match (a, b) {
+            (Left | Right, Left | Right) => self.horizontal,
+            (Up | Down, Up | Down) => self.vertical,
+            (Down, Right) | (Right, Down) => self.corner_down_right,
+            (Down, Left) | (Left, Down) => self.corner_down_left,
+            (Up, Right) | (Right, Up) => self.corner_up_right,
+            (Up, Left) | (Left, Up) => self.corner_up_left,
+            // A diagonal neighbour sets the slant; bends into a straight piece reuse it
+            (UpLeft | DownRight, _) | (_, UpLeft | DownRight) => self.falling,
+            (UpRight | DownLeft, _) | (_, UpRight | DownLeft) => self.rising,
         }
     }
+}
 
-    /// Main game logic update - called once per frame
-    fn update(&mut self) {
-        if self.status != GameStatus::Playing {
-            return;
-        }
-
-        // Don't move snake until player presses first arrow key
-        if self.waiting_for_start {
-            return;
-        }
+/// Whether the locale advertises UTF-8 (first set of LC_ALL, LC_CTYPE, LANG wins)
+fn locale_is_utf8() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .map(|value| {
+            let value = value.to_ascii_lowercase();
+            value.contains("utf-8") || value.contains("utf8")
+        })
+        .unwrap_or(false)
+}
 
-        // Update direction (prevents 180-degree turns within one frame)
-        self.direction = self.next_direction;
+/// ASCII progress bar like `[###-------]` for a fraction between 0 and 1
+fn fill_bar(fraction: f32, width: usize) -> String {
+    let filled = ((fraction * width as f32).round() as usize).min(width);
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(width - filled))
+}
 
-        // Calculate new head position
-        let head = self.snake.front().unwrap();
-        let new_head = match self.direction {
-            Direction::Up => Point {
-                x: head.x,
-                y: head.y - 1,
-            },
-            Direction::Down => Point {
-                x: head.x,
-                y: head.y + 1,
-            },
-            Direction::Left => Point {
-                x: head.x - 1,
-                y: head.y,
-            },
-            Direction::Right => Point {
-                x: head.x + 1,
-                y: head.y,
-            },
-        };
+/// When the last few frames were drawn, for the --debug FPS readout
+struct FrameTimer {
+    times: VecDeque<Instant>, // Oldest first, at most FPS_SAMPLE_FRAMES
+}
 
-        // Check wall collision
-        if new_head.x < 0
-            || new_head.x >= self.game_width
-            || new_head.y < 0
-            || new_head.y >= self.game_height
-        {
-            self.status = GameStatus::GameOver;
-            return;
-        }
+impl FrameTimer {
+    fn new() -> Self {
+        FrameTimer { times: VecDeque::with_capacity(FPS_SAMPLE_FRAMES) }
+    }
 
-        // Check self collision
-        if self.snake.iter().any(|segment| *segment == new_head) {
-            self.status = GameStatus::GameOver;
-            return;
+    /// Note that a frame is being drawn now
+    fn tick(&mut self) {
+        if self.times.len() == FPS_SAMPLE_FRAMES {
+            self.times.pop_front();
         }
+        self.times.push_back(Instant::now());
+    }
 
-        // Move snake
-        self.snake.push_front(new_head);
-
-        // Check if food was eaten
-        if new_head == self.food {
-            self.score += 10;
-            self.spawn_food();
-            // Don't remove tail - snake grows
-        } else {
-            // Remove tail - normal movement
-            self.snake.pop_back();
+    /// Frames per second over the recorded frames (0 until there are two)
+    fn fps(&self) -> f64 {
+        match (self.times.front(), self.times.back()) {
+            (Some(first), Some(last)) if last > first => {
+                (self.times.len() - 1) as f64 / (*last - *first).as_secs_f64()
+            }
+            _ => 0.0,
         }
     }
+}
 
-    fn toggle_pause(&mut self) {
-        self.status = match self.status {
-            GameStatus::Playing => GameStatus::Paused,
-            GameStatus::Paused => GameStatus::Playing,
-            GameStatus::GameOver => GameStatus::GameOver,
-        };
-    }
+/// Options offered by the pause menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PauseChoice {
+    Resume,
+    Restart,
+    Quit,
 }
 
-// ============================================================================
-// RENDERING LAYER
-// ============================================================================
+const PAUSE_OPTIONS: [(PauseChoice, &str); 3] = [
+    (PauseChoice::Resume, "Resume"),
+    (PauseChoice::Restart, "Restart"),
+    (PauseChoice::Quit, "Quit"),
+];
 
 struct Renderer {
     window: Window,
+    keys: KeyBindings, // Shown in hints and used to interpret gameplay input
+    theme: &'static Theme,
+    snake_glyphs: &'static SnakeGlyphs,
+    player2_glyphs: &'static SnakeGlyphs, // Same as snake_glyphs unless the palette wants distinct shapes
+    has_color: bool, // False on monochrome terminals; attributes stand in for colors
+    box_border: bool, // Walls drawn with box-drawing lines (UTF-8 terminals, unless --ascii)
+    rainbow: bool,   // Snake cycles through RAINBOW_PAIRS (only with enough colors)
+    grid: bool,      // Dotted checkerboard behind the playing field
+    wide: bool,      // Each cell is two columns wide, so the field looks closer to square
+    session_best: u32, // Best score of any game since the program started (restarts and menus included)
+    frame_timer: Option<FrameTimer>, // Set with --debug: frame number, FPS, and coordinates in the top-right corner
+    title: bool, // Set with --title: the score goes in the terminal's window title
+    fog: Option<i32>, // Set with --fog: only cells this close to a head are drawn
+    scroll: bool, // Set with --scroll: fields too big for the terminal show the slice around the heads
+    manual_step: bool, // Set with --step: the slow-mo key moves the snake one cell instead
+    vision: bool, // Set with --vision: the cells ahead of each head are tinted
+    afterimage: bool, // Set with --afterimage: the head leaves a short fading trail
+    afterimage_attrs: [chtype; game::HEAD_TRAIL_LENGTH], // Newest trail cell first; dimmer toward the end
+    fade: bool, // Set with --fade: a solo snake's body darkens from head to tail
+    fade_pairs: bool, // FADE_PAIRS are set up; without them the fade is bold and normal stripes
+    title_score: Option<u32>, // Score last written to the title, so it's only rewritten on a change
 }
 
 impl Renderer {
-    fn new() -> Result<Self, String> {
+    /// Set up curses on the terminal
+    /// Fails if stdin or stdout isn't a terminal (piped, redirected, or under CI), where curses can't run
+    fn new(
+        keys: KeyBindings,
+        theme: &'static Theme,
+        palette: &'static Palette,
+        rainbow: bool,
+        grid: bool,
+        wide: bool,
+        ascii: bool,
+    ) -> Result<Self, String> {
+        if !std::io::stdout().is_terminal() {
+            return Err("stdout is not a terminal; run the game in an interactive terminal, \
+                or use --headless with --autopilot or --replay"
+                .to_string());
+        }
+        if !std::io::stdin().is_terminal() {
+            return Err("stdin is not a terminal; the game reads the keyboard, \
+                so it can't run with piped input (--input-pipe takes directions from another program)"
+                .to_string());
+        }
+
         // Initialize curses
         let window = initscr();
 
@@ -279,27 +549,165 @@ impl Renderer {
         pancurses::cbreak(); // Disable line buffering
         window.keypad(true); // Enable arrow keys
         window.timeout(0); // Non-blocking input
+        // Report left clicks for the size menu; terminals without a mouse just never send them
+        pancurses::mousemask(pancurses::BUTTON1_CLICKED, std::ptr::null_mut());
 
         // Initialize colors if available
-        if pancurses::has_colors() {
+        let has_color = pancurses::has_colors();
+        if has_color {
             pancurses::start_color();
-            pancurses::init_pair(1, pancurses::COLOR_GREEN, pancurses::COLOR_BLACK); // Snake
-            pancurses::init_pair(2, pancurses::COLOR_RED, pancurses::COLOR_BLACK); // Food
-            pancurses::init_pair(3, pancurses::COLOR_YELLOW, pancurses::COLOR_BLACK); // Border
-            pancurses::init_pair(4, pancurses::COLOR_WHITE, pancurses::COLOR_BLACK); // Text
+            for &(pair, foreground, background) in &palette.pairs {
+                pancurses::init_pair(pair, foreground, background);
+            }
+        }
+
+        // The rainbow needs all eight basic colors plus its own pairs; otherwise the snake stays green
+        let rainbow = rainbow
+            && has_color
+            && pancurses::COLORS() >= 8
+            && pancurses::COLOR_PAIRS() > RAINBOW_PAIRS[RAINBOW_PAIRS.len() - 1] as i32;
+        if rainbow {
+            for (&pair, &color) in RAINBOW_PAIRS.iter().zip(&RAINBOW_COLORS) {
+                pancurses::init_pair(pair as i16, color, pancurses::COLOR_BLACK);
+            }
+        }
+
+        // The afterimage fades through grey where the terminal has the colors, and is
+        // simply dim otherwise
+        let fading = has_color
+            && pancurses::COLORS() >= 256
+            && pancurses::COLOR_PAIRS() > AFTERIMAGE_PAIRS[AFTERIMAGE_PAIRS.len() - 1] as i32;
+        let afterimage_attrs = if fading {
+            for (&pair, &color) in AFTERIMAGE_PAIRS.iter().zip(&AFTERIMAGE_COLORS) {
+                pancurses::init_pair(pair as i16, color, pancurses::COLOR_BLACK);
+            }
+            AFTERIMAGE_PAIRS.map(pancurses::COLOR_PAIR)
+        } else {
+            [pancurses::A_DIM; game::HEAD_TRAIL_LENGTH]
+        };
+
+        // The same goes for the snake's fade, in shades of its own color
+        let fade_pairs = has_color
+            && pancurses::COLORS() >= 256
+            && pancurses::COLOR_PAIRS() > FADE_PAIRS[FADE_PAIRS.len() - 1] as i32;
+        if fade_pairs {
+            for (&pair, &color) in FADE_PAIRS.iter().zip(&palette.fade_colors) {
+                pancurses::init_pair(pair as i16, color, pancurses::COLOR_BLACK);
+            }
+        }
+
+        // Fancy snake and wall glyphs only when the terminal can show them (and --ascii isn't set)
+        let unicode = !ascii && locale_is_utf8();
+        let (snake_glyphs, alt_glyphs) = if unicode {
+            (&UNICODE_SNAKE, &UNICODE_SNAKE_ALT)
+        } else {
+            (&ASCII_SNAKE, &ASCII_SNAKE_ALT)
+        };
+        let player2_glyphs = if palette.distinct_shapes { alt_glyphs } else { snake_glyphs };
+
+        Ok(Renderer {
+            window,
+            keys,
+            theme,
+            snake_glyphs,
+            player2_glyphs,
+            has_color,
+            box_border: unicode,
+            rainbow,
+            grid,
+            wide,
+            session_best: 0,
+            frame_timer: None,
+            title: false,
+            fog: None,
+            scroll: false,
+            manual_step: false,
+            vision: false,
+            afterimage: false,
+            afterimage_attrs,
+            fade: false,
+            fade_pairs,
+            title_score: None,
+        })
+    }
+
+    /// Attribute for a color pair, or a monochrome stand-in when the terminal has no colors
+    fn color(&self, pair: chtype) -> chtype {
+        if self.has_color {
+            return pancurses::COLOR_PAIR(pair);
+        }
+
+        match pair {
+            1 => pancurses::A_BOLD,      // Snake
+            2 => pancurses::A_REVERSE,   // Food, errors
+            6 => pancurses::A_UNDERLINE, // Player 2 snake, portals
+            _ => pancurses::A_NORMAL,    // Border, text, obstacles
+        }
+    }
+
+    /// Screen columns one board cell takes up (two with --wide)
+    fn cell_width(&self) -> i32 {
+        if self.wide { 2 } else { 1 }
+    }
+
+    /// Print one row of text, cut off at the info panel's width (and the terminal's edge)
+    /// so it never wraps onto the row below
+    fn print_clipped(&self, y: i32, x: i32, text: &str) {
+        let room = (self.window.get_max_x() - x).min(INFO_PANEL_WIDTH);
+        self.window.mvaddnstr(y, x, text, room.max(0));
+    }
+
+    /// Terminal columns and rows taken up by a field showing this many cells
+    fn screen_size(&self, width: i32, height: i32) -> (i32, i32) {
+        // Need space for: info panel (INFO_PANEL_HEIGHT lines, however narrow the field),
+        // borders (2 chars vertical, 2 horizontal), and the status messages below the field
+        // (up to 3 lines after a game)
+        ((width * self.cell_width() + 2).max(INFO_PANEL_WIDTH + 1), height + INFO_PANEL_HEIGHT + 5)
+    }
+
+    /// Terminal columns and rows a field of the given size needs (--scroll gets by with a slice)
+    fn required_size(&self, width: i32, height: i32) -> (i32, i32) {
+        if self.scroll {
+            self.screen_size(width.min(SCROLL_MIN_WIDTH), height.min(SCROLL_MIN_HEIGHT))
+        } else {
+            self.screen_size(width, height)
+        }
+    }
+
+    /// Board cells shown on screen: all of them, or with --scroll as many as the terminal holds
+    fn view_size(&self, width: i32, height: i32) -> (i32, i32) {
+        if !self.scroll {
+            return (width, height);
         }
+        // The borders take 2 columns; the panel and messages take rows above and below
+        let spare_y = self.screen_size(0, 0).1;
+        let columns = (self.window.get_max_x() - 2) / self.cell_width();
+        let rows = self.window.get_max_y() - spare_y;
+        (width.min(columns), height.min(rows))
+    }
 
-        Ok(Renderer { window })
+    /// Top-left board cell on screen: the view follows the heads, staying on the board
+    /// (always 0, 0 when the whole field is shown)
+    fn camera(&self, game: &GameState) -> (i32, i32) {
+        let (view_width, view_height) = self.view_size(game.game_width, game.game_height);
+        let heads = game.snakes.len() as i32;
+        let (sum_x, sum_y) = game.snakes.iter().fold((0, 0), |(x, y), snake| (x + snake.head().x, y + snake.head().y));
+        let follow = |sum: i32, view: i32, size: i32| (sum / heads - view / 2).clamp(0, (size - view).max(0));
+        (
+            follow(sum_x, view_width, game.game_width),
+            follow(sum_y, view_height, game.game_height),
+        )
     }
 
     fn check_size_fits(&self, size: &FieldSize) -> bool {
+        self.field_fits(size.width, size.height)
+    }
+
+    fn field_fits(&self, width: i32, height: i32) -> bool {
         let max_y = self.window.get_max_y();
         let max_x = self.window.get_max_x();
 
-        // Need space for: info panel (3 lines), borders (2 chars vertical, 2 horizontal)
-        let required_height = size.height + 5; // +3 for info, +2 for borders
-        let required_width = size.width + 2;   // +2 for borders
-
+        let (required_width, required_height) = self.required_size(width, height);
         max_y >= required_height && max_x >= required_width
     }
 
@@ -307,9 +715,8 @@ impl Renderer {
         let max_y = self.window.get_max_y();
         let max_x = self.window.get_max_x();
 
-        // Info panel takes 4 lines (3 lines + 1 blank)
-        let total_height = height + 5; // +3 for info, +2 for borders
-        let total_width = width + 2;   // +2 for borders
+        let (view_width, view_height) = self.view_size(width, height);
+        let (total_width, total_height) = self.screen_size(view_width, view_height);
 
         let offset_y = ((max_y - total_height) / 2).max(0);
         let offset_x = ((max_x - total_width) / 2).max(0);
@@ -317,26 +724,72 @@ impl Renderer {
         (offset_x, offset_y)
     }
 
-    fn show_size_menu(&self) -> Option<usize> {
+    /// Ask for the field size; `previous` (width, height) is highlighted and picked again with Enter
+    fn show_size_menu(&self, previous: Option<(i32, i32)>) -> Option<FieldSize> {
         // Use blocking input for menu (prevents flickering from tight loop)
         self.window.timeout(-1);
 
+        // Row of each entry, shared by the drawing and mouse clicks
+        let start_y = 2;
+        let option_y = |index: usize| start_y + 2 + (index as i32 * 2); // Presets, then custom
+        let custom_index = FIELD_SIZES.len();
+        let instructions_y = option_y(custom_index) + 3;
+        let demo_y = instructions_y + 5; // Below the quit question
+
+        // Room the menu needs: its title, and every row down to the quit question
+        let title = "=== RUST SNAKE - SELECT FIELD SIZE ===";
+        let (menu_width, menu_height) = (2 + title.len() as i32, instructions_y + 4);
+        let menu_fits = || self.window.get_max_x() >= menu_width && self.window.get_max_y() >= menu_height;
+
+        // Last game's size: a preset, or else the custom entry standing for it
+        let previous = previous.map(|(width, height)| {
+            FIELD_SIZES
+                .iter()
+                .find(|size| size.width == width && size.height == height)
+                .copied()
+                .unwrap_or(FieldSize {
+                    name: "Custom",
+                    width,
+                    height,
+                })
+        });
+        let highlighted = previous.map(|size| {
+            FIELD_SIZES.iter().position(|preset| *preset == size).unwrap_or(custom_index)
+        });
+        let highlight = |index: usize| {
+            if highlighted == Some(index) {
+                pancurses::A_REVERSE
+            } else {
+                pancurses::A_NORMAL
+            }
+        };
+
         // Helper function to draw the menu (called once per iteration only when needed)
         let draw_menu = || {
             self.window.clear();
 
-            let start_y = 2;
+            // Too small for the menu: just say so (redrawn on every resize)
+            if !menu_fits() {
+                self.window.attron(self.color(self.theme.alert_pair));
+                self.window.mvprintw(0, 0, "Please enlarge your terminal");
+                self.window.attroff(self.color(self.theme.alert_pair));
+                self.window.mvprintw(1, 0, format!("(need at least {}x{})", menu_width, menu_height));
+                self.window.mvprintw(2, 0, "Q quits");
+                self.window.refresh();
+                return;
+            }
+
             let start_x = 2;
 
             // Title
-            let color_pair = pancurses::COLOR_PAIR(4);
+            let color_pair = self.color(4);
             self.window.attron(color_pair);
-            self.window.mvprintw(start_y, start_x, "=== RUST SNAKE - SELECT FIELD SIZE ===");
+            self.window.mvprintw(start_y, start_x, title);
             self.window.attroff(color_pair);
 
             // Options
             for (i, size) in FIELD_SIZES.iter().enumerate() {
-                let y = start_y + 2 + (i as i32 * 2);
+                let y = option_y(i);
                 let option_text = format!(
                     "  {}. {} ({}x{})",
                     i + 1,
@@ -347,19 +800,35 @@ impl Renderer {
 
                 // Check if this size fits
                 if self.check_size_fits(size) {
-                    self.window.attron(pancurses::COLOR_PAIR(1));
+                    self.window.attron(self.color(1) | highlight(i));
                     self.window.mvprintw(y, start_x, &option_text);
-                    self.window.attroff(pancurses::COLOR_PAIR(1));
+                    self.window.attroff(self.color(1) | highlight(i));
                 } else {
-                    self.window.attron(pancurses::COLOR_PAIR(2));
-                    self.window.mvprintw(y, start_x, &format!("{} [TOO LARGE]", option_text));
-                    self.window.attroff(pancurses::COLOR_PAIR(2));
+                    self.window.attron(self.color(2) | highlight(i));
+                    self.window.mvprintw(y, start_x, format!("{} [TOO LARGE]", option_text));
+                    self.window.attroff(self.color(2) | highlight(i));
                 }
             }
 
+            // Custom size entry comes right after the presets (showing last game's custom size)
+            let custom_text = match previous {
+                Some(size) if highlighted == Some(custom_index) => {
+                    format!("  {}. Custom ({}x{}, or enter a new size)", custom_index + 1, size.width, size.height)
+                }
+                _ => format!("  {}. Custom (enter width and height)", custom_index + 1),
+            };
+            self.window.attron(self.color(4) | highlight(custom_index));
+            self.window.mvprintw(option_y(custom_index), start_x, custom_text);
+            self.window.attroff(self.color(4) | highlight(custom_index));
+
             // Instructions
-            let y = start_y + 2 + (FIELD_SIZES.len() as i32 * 2) + 1;
-            self.window.mvprintw(y, start_x, "Press 1-4 to select a size, or Q to quit");
+            let y = instructions_y;
+            let enter_hint = if previous.is_some() { ", Enter for the highlighted one" } else { "" };
+            self.window.mvprintw(
+                y,
+                start_x,
+                format!("Press 1-{} or click to select a size{}, or Q to quit", custom_index + 1, enter_hint),
+            );
 
             let terminal_info = format!(
                 "Terminal size: {}x{}",
@@ -374,161 +843,703 @@ impl Renderer {
         // Draw menu once before starting input loop
         draw_menu();
 
+        // Attract mode: a self-playing game under the menu once nothing was pressed for a while
+        let mut demo: Option<GameState> = None;
+        let mut idle_since = Instant::now();
+
         // Input loop - only redraws when necessary (after error dialog)
         loop {
-            // Block and wait for user input (no flickering)
-            match self.window.getch() {
-                Some(Input::Character('1')) => {
-                    if self.check_size_fits(&FIELD_SIZES[0]) {
-                        self.window.timeout(0); // Restore non-blocking for gameplay
-                        return Some(0);
-                    } else {
-                        self.show_size_error(&FIELD_SIZES[0]);
-                        draw_menu(); // Redraw menu after error dialog
+            // Wait for input in short ticks to run the demo; the dialogs opened below still block
+            self.window.timeout(ATTRACT_TICK_MS);
+            let input = self.window.getch();
+            self.window.timeout(-1);
+
+            if interrupted() {
+                self.window.timeout(0); // Restore non-blocking before exit
+                return None;
+            }
+            if input.is_none() {
+                if demo.is_none() && idle_since.elapsed() >= ATTRACT_DELAY && self.attract_demo_fits(demo_y) {
+                    demo = GameState::new(GameSettings::new(ATTRACT_WIDTH, ATTRACT_HEIGHT, 100), 0, 0).ok();
+                    if let Some(game) = &mut demo {
+                        game.skip_start_wait();
                     }
                 }
-                Some(Input::Character('2')) => {
-                    if self.check_size_fits(&FIELD_SIZES[1]) {
-                        self.window.timeout(0); // Restore non-blocking for gameplay
-                        return Some(1);
-                    } else {
-                        self.show_size_error(&FIELD_SIZES[1]);
-                        draw_menu(); // Redraw menu after error dialog
+                if let Some(game) = &mut demo {
+                    if game.is_finished() {
+                        game.restart();
+                    }
+                    if game.will_move() {
+                        if let Some(direction) = autopilot::choose_direction(game) {
+                            game.set_player_direction(0, direction);
+                        }
                     }
+                    game.update();
+                    self.draw_attract_demo(game, demo_y);
                 }
-                Some(Input::Character('3')) => {
-                    if self.check_size_fits(&FIELD_SIZES[2]) {
-                        self.window.timeout(0); // Restore non-blocking for gameplay
-                        return Some(2);
-                    } else {
-                        self.show_size_error(&FIELD_SIZES[2]);
-                        draw_menu(); // Redraw menu after error dialog
+                continue;
+            }
+
+            // Any key or click ends the demo (and is handled as usual)
+            idle_since = Instant::now();
+            if demo.take().is_some() {
+                draw_menu();
+            }
+
+            // Pick up the new terminal size; the menu (or the enlarge message) follows it
+            if input == Some(Input::KeyResize) {
+                pancurses::resize_term(0, 0);
+                draw_menu();
+                continue;
+            }
+            // Until the menu fits, Q is the only key that does anything (there's no room to ask)
+            if !menu_fits() {
+                if matches!(input, Some(Input::Character('q' | 'Q'))) {
+                    self.window.timeout(0); // Restore non-blocking before exit
+                    return None;
+                }
+                continue;
+            }
+
+            let selected = match input {
+                // This is the first screen, so quitting here leaves the game: ask first
+                Some(Input::Character('q')) | Some(Input::Character('Q')) => {
+                    if self.confirm_menu_quit(instructions_y + 3) {
+                        self.window.timeout(0); // Restore non-blocking before exit
+                        return None;
                     }
+                    None
                 }
-                Some(Input::Character('4')) => {
-                    if self.check_size_fits(&FIELD_SIZES[3]) {
+                // Enter plays on last game's size again (custom ones included, without asking)
+                Some(Input::Character('\n')) | Some(Input::KeyEnter) => match previous {
+                    Some(size) if self.check_size_fits(&size) => {
                         self.window.timeout(0); // Restore non-blocking for gameplay
-                        return Some(3);
-                    } else {
-                        self.show_size_error(&FIELD_SIZES[3]);
+                        return Some(size);
+                    }
+                    Some(size) => {
+                        self.show_size_error(&size);
                         draw_menu(); // Redraw menu after error dialog
+                        None
+                    }
+                    None => None,
+                },
+                // Number keys select the matching size, or the custom entry after them
+                Some(Input::Character(c)) => Self::size_index_for_key(c)
+                    .or_else(|| (c.to_digit(10) == Some(custom_index as u32 + 1)).then_some(custom_index)),
+                // A click on an entry's row selects it; one on the instructions quits
+                Some(Input::KeyMouse) => match pancurses::getmouse() {
+                    Ok(event) if event.y == instructions_y => {
+                        if self.confirm_menu_quit(instructions_y + 3) {
+                            self.window.timeout(0); // Restore non-blocking before exit
+                            return None;
+                        }
+                        None
                     }
+                    Ok(event) => (0..=custom_index).find(|&index| option_y(index) == event.y),
+                    Err(_) => None,
+                },
+                _ => None, // Invalid input - don't redraw, just wait for next input
+            };
+
+            match selected {
+                Some(index) if index == custom_index => {
+                    if let Some(size) = self.get_custom_size() {
+                        if self.check_size_fits(&size) {
+                            self.window.timeout(0); // Restore non-blocking for gameplay
+                            return Some(size);
+                        }
+                        self.show_size_error(&size);
+                    }
+                    draw_menu(); // Redraw menu after the custom size dialogs
                 }
-                Some(Input::Character('q')) | Some(Input::Character('Q')) => {
-                    self.window.timeout(0); // Restore non-blocking before exit
-                    return None;
+                Some(index) => {
+                    if self.check_size_fits(&FIELD_SIZES[index]) {
+                        self.window.timeout(0); // Restore non-blocking for gameplay
+                        return Some(FIELD_SIZES[index]);
+                    }
+                    self.show_size_error(&FIELD_SIZES[index]);
+                    draw_menu(); // Redraw menu after error dialog
                 }
+                None => {}
+            }
+        }
+    }
+
+    /// True if the attract demo's board fits on the screen from the given row down
+    fn attract_demo_fits(&self, top: i32) -> bool {
+        self.window.get_max_y() >= top + ATTRACT_HEIGHT + 2 && self.window.get_max_x() >= ATTRACT_WIDTH + 4
+    }
+
+    /// Draw the size menu's self-playing demo in a small bordered box from the given row down
+    fn draw_attract_demo(&self, game: &GameState, top: i32) {
+        let left = 2;
+        let border = self.theme.border.to_string();
+        let line = |unicode: &'static str| if self.box_border { unicode } else { border.as_str() };
+
+        let border_color = self.color(self.theme.border_pair);
+        self.window.attron(border_color);
+        for x in 0..ATTRACT_WIDTH + 2 {
+            let (upper, lower) = match x {
+                0 => (line("┌"), line("└")),
+                _ if x == ATTRACT_WIDTH + 1 => (line("┐"), line("┘")),
+                _ => (line("─"), line("─")),
+            };
+            self.window.mvaddstr(top, left + x, upper);
+            self.window.mvaddstr(top + ATTRACT_HEIGHT + 1, left + x, lower);
+        }
+        for y in 1..=ATTRACT_HEIGHT {
+            self.window.mvaddstr(top + y, left, line("│"));
+            self.window.mvaddstr(top + y, left + ATTRACT_WIDTH + 1, line("│"));
+            self.window.mvaddstr(top + y, left + 1, " ".repeat(ATTRACT_WIDTH as usize));
+        }
+        self.window.attroff(border_color);
+
+        let cell = |point: &Point| (top + 1 + point.y, left + 1 + point.x);
+
+        let food_color = self.color(self.theme.food_pair);
+        self.window.attron(food_color);
+        for food in &game.food {
+            let (y, x) = cell(food);
+            self.window.mvaddch(y, x, self.theme.food);
+        }
+        self.window.attroff(food_color);
+
+        let snake = game.snake();
+        let snake_color = self.color(self.theme.snake_pair);
+        self.window.attron(snake_color);
+        for (i, segment) in snake.body().iter().enumerate() {
+            let toward = |neighbour: Option<&Point>| {
+                neighbour.and_then(|n| segment.direction_to(n, ATTRACT_WIDTH, ATTRACT_HEIGHT))
+            };
+            let to_head = if i > 0 { toward(snake.body().get(i - 1)) } else { None };
+            let to_tail = toward(snake.body().get(i + 1));
+            let glyph = match (i, to_head, to_tail) {
+                (0, _, _) => self.snake_glyphs.head(snake.direction),
+                (_, Some(a), Some(b)) => self.snake_glyphs.body(a, b),
+                (_, Some(a), None) | (_, None, Some(a)) => self.snake_glyphs.body(a, a),
+                (_, None, None) => self.snake_glyphs.body(snake.direction, snake.direction),
+            };
+            let (y, x) = cell(segment);
+            self.window.mvaddstr(y, x, glyph);
+        }
+        self.window.attroff(snake_color);
+
+        self.window.refresh();
+    }
+
+    /// Ask "Quit? (y/n)" on the given row of a menu; only y confirms
+    /// Any other key clears the question again and the menu carries on
+    fn confirm_menu_quit(&self, y: i32) -> bool {
+        self.window.attron(self.color(self.theme.alert_pair));
+        self.window.mvprintw(y, 2, "Quit? (y/n)");
+        self.window.attroff(self.color(self.theme.alert_pair));
+        self.window.refresh();
+
+        // Ctrl+C (no key) needs no confirming
+        let confirmed = matches!(self.wait_for_key(), Some(Input::Character('y' | 'Y')) | None);
+        self.window.mv(y, 0);
+        self.window.clrtoeol();
+        self.window.refresh();
+        confirmed
+    }
+
+    /// Map a number key ('1', '2', ...) to an index into FIELD_SIZES
+    fn size_index_for_key(c: char) -> Option<usize> {
+        let digit = c.to_digit(10)? as usize;
+        if (1..=FIELD_SIZES.len()).contains(&digit) {
+            Some(digit - 1)
+        } else {
+            None
+        }
+    }
+
+    /// Ask for a custom width and height
+    /// Returns None if the user cancels (Q) or enters an invalid value (after showing an error)
+    fn get_custom_size(&self) -> Option<FieldSize> {
+        self.window.clear();
+
+        let start_y = 2;
+        let start_x = 2;
+
+        // Title
+        self.window.attron(self.color(4));
+        self.window.mvprintw(start_y, start_x, "=== CUSTOM FIELD SIZE ===");
+        self.window.attroff(self.color(4));
+
+        // The largest field that fits, worked out by the same check the entered size gets
+        let max_width = CUSTOM_WIDTH_RANGE.rev().find(|&width| self.field_fits(width, *CUSTOM_HEIGHT_RANGE.start()));
+        let max_height = CUSTOM_HEIGHT_RANGE.rev().find(|&height| self.field_fits(*CUSTOM_WIDTH_RANGE.start(), height));
+        let room = match (max_width, max_height) {
+            (Some(width), Some(height)) => format!("fits fields up to {}x{}", width, height),
+            _ => "too small for any field".to_string(),
+        };
+        self.window.mvprintw(
+            start_y + 2,
+            start_x,
+            format!("Terminal size: {}x{} ({})", self.window.get_max_x(), self.window.get_max_y(), room),
+        );
+        self.window.mvprintw(start_y + 4, start_x, "Press Q to cancel and return to the size menu");
+
+        let prompts = [
+            (
+                format!("Width ({}-{}): ", CUSTOM_WIDTH_RANGE.start(), CUSTOM_WIDTH_RANGE.end()),
+                CUSTOM_WIDTH_RANGE,
+            ),
+            (
+                format!("Height ({}-{}): ", CUSTOM_HEIGHT_RANGE.start(), CUSTOM_HEIGHT_RANGE.end()),
+                CUSTOM_HEIGHT_RANGE,
+            ),
+        ];
+
+        let mut dimensions = [0; 2];
+        for (i, (prompt, range)) in prompts.iter().enumerate() {
+            let y = start_y + 6 + i as i32;
+            self.window.mvprintw(y, start_x, prompt);
+            self.window.refresh();
+
+            let input = self.read_text_input(y, start_x + prompt.len() as i32)?;
+            match input.trim().parse::<i32>() {
+                Ok(value) if range.contains(&value) => dimensions[i] = value,
                 _ => {
-                    // Invalid input - don't redraw, just wait for next input
+                    self.show_input_error(&format!(
+                        "Invalid size '{}'. Please enter a number between {} and {}.",
+                        input.trim(),
+                        range.start(),
+                        range.end()
+                    ));
+                    return None;
                 }
             }
         }
+
+        Some(FieldSize {
+            name: "Custom",
+            width: dimensions[0],
+            height: dimensions[1],
+        })
     }
 
     fn show_size_error(&self, size: &FieldSize) {
         // Error dialog uses blocking input (already set by show_size_menu)
         self.window.clear();
 
-        let color_pair = pancurses::COLOR_PAIR(2);
+        let color_pair = self.color(2);
         self.window.attron(color_pair);
 
-        let required_width = size.width + 2;
-        let required_height = size.height + 5;
+        let (required_width, required_height) = self.required_size(size.width, size.height);
 
         self.window.mvprintw(2, 2, "ERROR: Terminal too small for this field size!");
         self.window.attroff(color_pair);
 
-        self.window.mvprintw(4, 2, &format!("Selected: {} ({}x{})", size.name, size.width, size.height));
-        self.window.mvprintw(5, 2, &format!("Required: {}x{}", required_width, required_height));
-        self.window.mvprintw(6, 2, &format!("Current:  {}x{}", self.window.get_max_x(), self.window.get_max_y()));
+        self.window.mvprintw(4, 2, format!("Selected: {} ({}x{})", size.name, size.width, size.height));
+        self.window.mvprintw(5, 2, format!("Required: {}x{}", required_width, required_height));
+        self.window.mvprintw(6, 2, format!("Current:  {}x{}", self.window.get_max_x(), self.window.get_max_y()));
 
         self.window.mvprintw(8, 2, "Please resize your terminal or select a smaller field size.");
         self.window.mvprintw(9, 2, "Press any key to return to the menu...");
 
         self.window.refresh();
-        // Block and wait for any key press (no timeout needed since parent set it)
-        self.window.getch();
+        // Wait for any key press
+        self.wait_for_key();
     }
 
-    fn show_difficulty_menu(&self) -> Option<usize> {
+    /// Show a menu of lettered options, each with a one-line description
+    /// Returns the index of the chosen option, or None if user quits (Q)
+    fn show_choice_menu(&self, title: &str, options: &[(char, &str, &str)], prompt: &str) -> Option<usize> {
         // Use blocking input for menu (prevents flickering)
         self.window.timeout(-1);
 
-        // Helper function to draw the menu
-        let draw_menu = || {
-            self.window.clear();
-
-            let start_y = 2;
-            let start_x = 2;
-
-            // Title
-            let color_pair = pancurses::COLOR_PAIR(4);
-            self.window.attron(color_pair);
-            self.window.mvprintw(start_y, start_x, "=== SELECT DIFFICULTY ===");
-            self.window.attroff(color_pair);
+        self.window.clear();
 
-            // Options
-            for (i, difficulty) in DIFFICULTIES.iter().enumerate() {
-                let y = start_y + 2 + (i as i32 * 3);
-                let option_text = format!("  {}. {}", i + 1, difficulty.name);
+        let start_y = 2;
+        let start_x = 2;
+
+        // Title
+        self.window.attron(self.color(4));
+        self.window.mvprintw(start_y, start_x, format!("=== {} ===", title));
+        self.window.attroff(self.color(4));
+
+        // Options
+        for (i, (key, name, description)) in options.iter().enumerate() {
+            let y = start_y + 2 + (i as i32 * 3);
+            self.window.attron(self.color(1));
+            self.window.mvprintw(y, start_x, format!("  {}. {}", key, name));
+            self.window.attroff(self.color(1));
+            self.window.mvprintw(y + 1, start_x, format!("     {}", description));
+        }
 
-                // Color based on difficulty level
-                let option_color = match i {
-                    0 => pancurses::COLOR_PAIR(1), // Easy - green
-                    1 => pancurses::COLOR_PAIR(3), // Medium - yellow
-                    2 => pancurses::COLOR_PAIR(2), // Hard - red
-                    3 => pancurses::COLOR_PAIR(4), // Custom - white/cyan
-                    _ => pancurses::COLOR_PAIR(4),
-                };
+        // Instructions
+        let y = start_y + 2 + (options.len() as i32 * 3);
+        self.window.mvprintw(y, start_x, format!("{} Press Q to quit", prompt));
 
-                self.window.attron(option_color);
-                self.window.mvprintw(y, start_x, &option_text);
-                self.window.attroff(option_color);
+        self.window.refresh();
+
+        // Input loop
+        loop {
+            match self.wait_for_key() {
+                // No key means Ctrl+C
+                None | Some(Input::Character('q')) | Some(Input::Character('Q')) => {
+                    self.window.timeout(0); // Restore non-blocking before exit
+                    return None;
+                }
+                Some(Input::Character(c)) => {
+                    let pressed = c.to_ascii_uppercase();
+                    if let Some(index) = options.iter().position(|(key, _, _)| *key == pressed) {
+                        self.window.timeout(0); // Restore non-blocking for gameplay
+                        return Some(index);
+                    }
+                }
+                _ => {
+                    // Invalid input - just wait for next input
+                }
+            }
+        }
+    }
+
+    /// Ask whether the walls are solid, wrap around, or have doorways, along with the options
+    /// for what else is on the board, which keep their defaults unless toggled by their letter
+    /// Returns None if user quits (Q)
+    fn show_wall_mode_menu(&self) -> Option<BoardChoice> {
+        // Use blocking input for menu (prevents flickering)
+        self.window.timeout(-1);
+
+        let modes = [
+            ('S', "Solid", "Hitting a wall ends the game"),
+            ('W', "Wrap", "Leave one edge to re-enter on the opposite side"),
+            ('D', "Doorways", "Solid walls with a gap in the middle of each side"),
+        ];
+        let mut choice = BoardChoice {
+            wrap_walls: false, // Set when a wall mode is picked
+            doorways: false,
+            layout: Layout::Open,
+            hazard_trail: false,
+            portals: false,
+            diagonal: false,
+            shrinking: false,
+            poison: 0,
+            tron: false,
+        };
+
+        // Helper function to draw the menu
+        let draw_menu = |choice: &BoardChoice| {
+            self.window.clear();
+
+            let start_y = 2;
+            let start_x = 2;
+
+            // Title
+            self.window.attron(self.color(4));
+            self.window.mvprintw(start_y, start_x, "=== SELECT WALL MODE ===");
+            self.window.attroff(self.color(4));
+
+            // Options
+            for (i, (key, name, description)) in modes.iter().enumerate() {
+                let y = start_y + 2 + (i as i32 * 3);
+                self.window.attron(self.color(1));
+                self.window.mvprintw(y, start_x, format!("  {}. {}", key, name));
+                self.window.attroff(self.color(1));
+                self.window.mvprintw(y + 1, start_x, format!("     {}", description));
+            }
+
+            // Toggles, one line each with their current setting
+            let y = start_y + 2 + (modes.len() as i32 * 3);
+            let toggles = [
+                match choice.layout {
+                    Layout::Open => "  O. Obstacles: None (an open field)",
+                    Layout::Blocks => "  O. Obstacles: Blocks (four blocks to steer around)",
+                    Layout::Campaign => "  O. Obstacles: Campaign (four walled levels, each with a goal score)",
+                },
+                if choice.hazard_trail {
+                    "  H. Hazards: Trail (every eaten food leaves a deadly hazard behind)"
+                } else {
+                    "  H. Hazards: Off (eaten food simply disappears)"
+                },
+                if choice.portals {
+                    "  P. Portals: On (enter one portal to come out of the other)"
+                } else {
+                    "  P. Portals: None (no teleporting)"
+                },
+                if choice.diagonal {
+                    "  M. Movement: Eight-way (numpad 7/9/1/3 also move diagonally)"
+                } else {
+                    "  M. Movement: Four-way (classic up, down, left, right)"
+                },
+                if choice.shrinking {
+                    "  A. Arena: Shrinking (the walls close in every 15 seconds)"
+                } else {
+                    "  A. Arena: Fixed (the walls stay where they are)"
+                },
+                match choice.poison {
+                    0 => "  F. Poison food: None (only good food on the board)",
+                    1 => "  F. Poison food: Rare (now and then; it cuts off the tail and costs points)",
+                    _ => "  F. Poison food: Frequent (poison turns up often)",
+                },
+                if choice.tron {
+                    "  T. Tail: Tron (every cell the tail leaves becomes a permanent wall)"
+                } else {
+                    "  T. Tail: Normal (the tail simply follows the snake)"
+                },
+            ];
+            for (i, toggle) in toggles.iter().enumerate() {
+                self.window.mvprintw(y + i as i32, start_x, toggle);
+            }
+
+            // Instructions
+            self.window.mvprintw(
+                y + toggles.len() as i32 + 1,
+                start_x,
+                "Press S, W, or D for the walls, a letter to change an option, or Q to quit",
+            );
+
+            self.window.refresh();
+        };
+
+        // Draw menu once before starting input loop
+        draw_menu(&choice);
+
+        // Input loop
+        loop {
+            // Block and wait for user input (no flickering); no key means Ctrl+C
+            match self.wait_for_key() {
+                None | Some(Input::Character('q')) | Some(Input::Character('Q')) => {
+                    self.window.timeout(0); // Restore non-blocking before exit
+                    return None;
+                }
+                Some(Input::Character('o')) | Some(Input::Character('O')) => {
+                    choice.layout = match choice.layout {
+                        Layout::Open => Layout::Blocks,
+                        Layout::Blocks => Layout::Campaign,
+                        Layout::Campaign => Layout::Open,
+                    };
+                    draw_menu(&choice);
+                }
+                Some(Input::Character('h')) | Some(Input::Character('H')) => {
+                    choice.hazard_trail = !choice.hazard_trail;
+                    draw_menu(&choice);
+                }
+                Some(Input::Character('p')) | Some(Input::Character('P')) => {
+                    choice.portals = !choice.portals;
+                    draw_menu(&choice);
+                }
+                Some(Input::Character('m')) | Some(Input::Character('M')) => {
+                    choice.diagonal = !choice.diagonal;
+                    draw_menu(&choice);
+                }
+                Some(Input::Character('a')) | Some(Input::Character('A')) => {
+                    choice.shrinking = !choice.shrinking;
+                    draw_menu(&choice);
+                }
+                Some(Input::Character('f')) | Some(Input::Character('F')) => {
+                    choice.poison = (choice.poison + 1) % POISON_CHANCES.len();
+                    draw_menu(&choice);
+                }
+                Some(Input::Character('t')) | Some(Input::Character('T')) => {
+                    choice.tron = !choice.tron;
+                    draw_menu(&choice);
+                }
+                Some(Input::Character(c)) => {
+                    // A wall mode's letter picks it; anything else is ignored
+                    let pressed = c.to_ascii_uppercase();
+                    if let Some(index) = modes.iter().position(|(key, _, _)| *key == pressed) {
+                        choice.wrap_walls = index == 1;
+                        choice.doorways = index == 2;
+                        self.window.timeout(0); // Restore non-blocking for gameplay
+                        return Some(choice);
+                    }
+                }
+                _ => {
+                    // Invalid input - don't redraw, just wait for next input
+                }
+            }
+        }
+    }
+
+    /// Ask for one player or two players sharing the board
+    /// Returns true for two players, or None if user quits (Q)
+    fn show_players_menu(&self) -> Option<bool> {
+        let choice = self.show_choice_menu(
+            "SELECT PLAYERS",
+            &[
+                ('1', "One player", "Classic solo snake"),
+                ('2', "Two players", "Arrows vs WASD - last snake alive wins"),
+            ],
+            "Players: (1) or (2)?",
+        )?;
+        Some(choice == 1)
+    }
+
+    /// Ask for the speed (unless --speed set it already) along with the options that go with
+    /// the difficulty, which keep their defaults unless toggled by their letter
+    /// Returns None if user quits (Q)
+    fn show_difficulty_menu(&self, speed: Option<u64>) -> Option<DifficultyChoice> {
+        // Use blocking input for menu (prevents flickering)
+        self.window.timeout(-1);
+
+        let mut choice = DifficultyChoice {
+            delay_ms: speed.unwrap_or(0), // Set when a difficulty is picked
+            growth_per_food: 1,
+            allow_pause: true,
+            assist: false,
+            speed_curve: SpeedCurve::Score,
+            hunger: 0,
+            forgiving: false,
+        };
+
+        // Helper function to draw the menu
+        let draw_menu = |choice: &DifficultyChoice| {
+            self.window.clear();
+
+            let start_y = 2;
+            let start_x = 2;
+
+            // Title
+            let color_pair = self.color(4);
+            self.window.attron(color_pair);
+            self.window.mvprintw(start_y, start_x, "=== SELECT DIFFICULTY ===");
+            self.window.attroff(color_pair);
+
+            // Options (with --speed, just the delay it gave)
+            let difficulties: &[Difficulty] = match speed {
+                Some(delay_ms) => {
+                    self.window.mvprintw(start_y + 2, start_x, format!("  Speed: {}ms (set with --speed)", delay_ms));
+                    &[]
+                }
+                None => &DIFFICULTIES,
+            };
+            for (i, difficulty) in difficulties.iter().enumerate() {
+                let y = start_y + 2 + (i as i32 * 3);
+                let option_text = format!("  {}. {}", i + 1, difficulty.name);
+
+                // Color based on difficulty level
+                let option_color = match i {
+                    0 => self.color(1), // Easy - green
+                    1 => self.color(3), // Medium - yellow
+                    2 => self.color(2), // Hard - red
+                    _ => self.color(4), // Custom - white/cyan
+                };
+
+                self.window.attron(option_color);
+                self.window.mvprintw(y, start_x, &option_text);
+                self.window.attroff(option_color);
 
                 // Description (skip delay_ms for Custom option)
-                let desc_text = if i == 3 {
+                let desc_text = if i == CUSTOM_DIFFICULTY {
                     format!("     {}", difficulty.description)
                 } else {
                     format!("     {} ({}ms delay)", difficulty.description, difficulty.delay_ms)
                 };
-                self.window.attron(pancurses::COLOR_PAIR(4));
+                self.window.attron(self.color(4));
                 self.window.mvprintw(y + 1, start_x, &desc_text);
-                self.window.attroff(pancurses::COLOR_PAIR(4));
+                self.window.attroff(self.color(4));
+            }
+
+            // Toggles, one line each with their current setting
+            let y = start_y + 2 + (difficulties.len().max(1) as i32 * 3);
+            let growth = GROWTH_DESCRIPTIONS[choice.growth_per_food as usize - 1];
+            let toggles = [
+                format!("  G. Growth: +{} per food ({})", choice.growth_per_food, growth),
+                if choice.allow_pause {
+                    "  H. Pausing: Normal (pause any time, idle games pause themselves)"
+                } else {
+                    "  H. Pausing: Hardcore (no turning back - the run can't be paused)"
+                }
+                .to_string(),
+                if choice.assist {
+                    "  A. Assist: On (the cell ahead lights up red before a deadly move)"
+                } else {
+                    "  A. Assist: Off (no help - you see a crash when it happens)"
+                }
+                .to_string(),
+                match choice.speed_curve {
+                    SpeedCurve::Score => "  S. Speed-up: By score (a little faster every 50 points)",
+                    SpeedCurve::Length => "  S. Speed-up: By length (faster the more of the board the snake fills)",
+                }
+                .to_string(),
+                match HUNGER_LIMITS[choice.hunger] {
+                    0 => "  T. Hunger: Off (the snake never goes hungry)".to_string(),
+                    limit => format!(
+                        "  T. Hunger: {} (eat within {} moves or lose a tail segment)",
+                        HUNGER_NAMES[choice.hunger], limit
+                    ),
+                },
+                if choice.forgiving {
+                    format!("  R. Rules: Forgiving (your first {} self-collisions are forgiven)", FORGIVING_COLLISIONS)
+                } else {
+                    "  R. Rules: Classic (running into yourself ends the game)".to_string()
+                },
+            ];
+            for (i, toggle) in toggles.iter().enumerate() {
+                self.window.mvprintw(y + i as i32, start_x, toggle);
             }
 
             // Instructions
-            let y = start_y + 2 + (DIFFICULTIES.len() as i32 * 3) + 1;
-            self.window.mvprintw(y, start_x, "Press 1-4 to select difficulty, or Q to quit");
+            let prompt = if speed.is_some() {
+                "Press Enter to go on".to_string()
+            } else {
+                format!("Press 1-{} to select difficulty", DIFFICULTIES.len())
+            };
+            self.window.mvprintw(
+                y + toggles.len() as i32 + 1,
+                start_x,
+                format!("{}, a letter to change an option, or Q to quit", prompt),
+            );
 
             self.window.refresh();
         };
 
         // Draw menu once before starting input loop
-        draw_menu();
+        draw_menu(&choice);
 
         // Input loop
         loop {
-            // Block and wait for user input (no flickering)
-            match self.window.getch() {
-                Some(Input::Character('1')) => {
-                    self.window.timeout(0); // Restore non-blocking for gameplay
-                    return Some(0);
+            // Block and wait for user input (no flickering); no key means Ctrl+C
+            match self.wait_for_key() {
+                None | Some(Input::Character('q')) | Some(Input::Character('Q')) => {
+                    self.window.timeout(0); // Restore non-blocking before exit
+                    return None;
                 }
-                Some(Input::Character('2')) => {
-                    self.window.timeout(0); // Restore non-blocking for gameplay
-                    return Some(1);
+                Some(Input::Character('g')) | Some(Input::Character('G')) => {
+                    choice.growth_per_food = choice.growth_per_food % GROWTH_DESCRIPTIONS.len() as u32 + 1;
+                    draw_menu(&choice);
                 }
-                Some(Input::Character('3')) => {
-                    self.window.timeout(0); // Restore non-blocking for gameplay
-                    return Some(2);
+                Some(Input::Character('h')) | Some(Input::Character('H')) => {
+                    choice.allow_pause = !choice.allow_pause;
+                    draw_menu(&choice);
                 }
-                Some(Input::Character('4')) => {
-                    // Keep blocking mode for custom input dialog
-                    return Some(3);
+                Some(Input::Character('a')) | Some(Input::Character('A')) => {
+                    choice.assist = !choice.assist;
+                    draw_menu(&choice);
                 }
-                Some(Input::Character('q')) | Some(Input::Character('Q')) => {
-                    self.window.timeout(0); // Restore non-blocking before exit
-                    return None;
+                Some(Input::Character('s')) | Some(Input::Character('S')) => {
+                    choice.speed_curve = match choice.speed_curve {
+                        SpeedCurve::Score => SpeedCurve::Length,
+                        SpeedCurve::Length => SpeedCurve::Score,
+                    };
+                    draw_menu(&choice);
+                }
+                Some(Input::Character('t')) | Some(Input::Character('T')) => {
+                    choice.hunger = (choice.hunger + 1) % HUNGER_LIMITS.len();
+                    draw_menu(&choice);
+                }
+                Some(Input::Character('r')) | Some(Input::Character('R')) => {
+                    choice.forgiving = !choice.forgiving;
+                    draw_menu(&choice);
+                }
+                Some(Input::Character('\n')) | Some(Input::KeyEnter) if speed.is_some() => {
+                    self.window.timeout(0); // Restore non-blocking for gameplay
+                    return Some(choice);
+                }
+                Some(Input::Character(c)) if speed.is_none() => {
+                    // Number keys select the matching difficulty; anything else is ignored
+                    if let Some(index) = Self::difficulty_index_for_key(c) {
+                        choice.delay_ms = if index == CUSTOM_DIFFICULTY {
+                            self.get_custom_delay()?
+                        } else {
+                            DIFFICULTIES[index].delay_ms
+                        };
+                        self.window.timeout(0); // Restore non-blocking for gameplay
+                        return Some(choice);
+                    }
                 }
                 _ => {
                     // Invalid input - don't redraw, just wait for next input
@@ -537,6 +1548,16 @@ impl Renderer {
         }
     }
 
+    /// Map a number key ('1', '2', ...) to an index into DIFFICULTIES
+    fn difficulty_index_for_key(c: char) -> Option<usize> {
+        let digit = c.to_digit(10)? as usize;
+        if (1..=DIFFICULTIES.len()).contains(&digit) {
+            Some(digit - 1)
+        } else {
+            None
+        }
+    }
+
     /// Get custom delay value from user input (10-1000ms)
     /// Returns Some(delay_ms) on success, None if user cancels (Q)
     fn get_custom_delay(&self) -> Option<u64> {
@@ -550,9 +1571,9 @@ impl Renderer {
             let start_x = 2;
 
             // Title
-            self.window.attron(pancurses::COLOR_PAIR(4));
+            self.window.attron(self.color(4));
             self.window.mvprintw(start_y, start_x, "=== CUSTOM DIFFICULTY ===");
-            self.window.attroff(pancurses::COLOR_PAIR(4));
+            self.window.attroff(self.color(4));
 
             // Instructions
             self.window.mvprintw(start_y + 2, start_x, "Enter delay in milliseconds (10-1000):");
@@ -565,70 +1586,32 @@ impl Renderer {
             self.window.mvprintw(start_y + 10, start_x, "Delay (ms): ");
             self.window.refresh();
 
-            // Enable echo and cursor for input
-            pancurses::echo();
-            pancurses::curs_set(1);
-
             // Read user input as a string
-            let mut input = String::new();
             let input_x = start_x + 12; // Position after "Delay (ms): "
-            self.window.mv(start_y + 10, input_x);
-
-            // Read characters one by one
-            loop {
-                match self.window.getch() {
-                    Some(Input::Character('\n')) | Some(Input::KeyEnter) => {
-                        break; // User pressed Enter
-                    }
-                    Some(Input::Character('q')) | Some(Input::Character('Q')) => {
-                        if input.is_empty() {
-                            // Q pressed with no input = cancel
-                            pancurses::noecho();
-                            pancurses::curs_set(0);
-                            self.window.timeout(0); // Restore non-blocking
-                            return None;
-                        } else {
-                            // Q is part of input (though invalid as number)
-                            input.push('q');
-                        }
-                    }
-                    Some(Input::Character(c)) if c.is_ascii_digit() || c == '\x08' || c == '\x7f' => {
-                        // Allow digits and backspace
-                        if c == '\x08' || c == '\x7f' {
-                            // Backspace
-                            input.pop();
-                        } else {
-                            input.push(c);
-                        }
-                    }
-                    Some(Input::Character(c)) => {
-                        // Allow other characters but they'll cause parse error
-                        input.push(c);
-                    }
-                    _ => {}
+            let input = match self.read_text_input(start_y + 10, input_x) {
+                Some(input) => input,
+                None => {
+                    self.window.timeout(0); // Restore non-blocking
+                    return None;
                 }
-            }
-
-            // Disable echo and cursor
-            pancurses::noecho();
-            pancurses::curs_set(0);
+            };
 
             // Parse and validate input
             match input.trim().parse::<u64>() {
-                Ok(delay) if delay >= 10 && delay <= 1000 => {
+                Ok(delay) if CUSTOM_DELAY_RANGE.contains(&delay) => {
                     self.window.timeout(0); // Restore non-blocking for gameplay
                     return Some(delay);
                 }
                 Ok(delay) => {
                     // Out of range
-                    self.show_custom_delay_error(&format!(
+                    self.show_input_error(&format!(
                         "Delay {} is out of range. Please enter a value between 10 and 1000.",
                         delay
                     ));
                 }
                 Err(_) => {
                     // Invalid input
-                    self.show_custom_delay_error(&format!(
+                    self.show_input_error(&format!(
                         "Invalid input: '{}'. Please enter a number between 10 and 1000.",
                         input.trim()
                     ));
@@ -637,11 +1620,68 @@ impl Renderer {
         }
     }
 
-    /// Show error message for invalid custom delay input
-    fn show_custom_delay_error(&self, message: &str) {
+    /// Read a line of typed text at (y, x) with echo and a visible cursor
+    /// Returns None if the user cancels by pressing Q before typing anything
+    fn read_text_input(&self, y: i32, x: i32) -> Option<String> {
+        // Enable echo and cursor for input
+        pancurses::echo();
+        pancurses::curs_set(1);
+
+        let mut input = String::new();
+        self.window.mv(y, x);
+
+        // Read characters one by one
+        loop {
+            match self.wait_for_key() {
+                Some(Input::Character('\n')) | Some(Input::KeyEnter) => {
+                    break; // User pressed Enter
+                }
+                None => {
+                    // Ctrl+C cancels
+                    pancurses::noecho();
+                    pancurses::curs_set(0);
+                    return None;
+                }
+                Some(Input::Character('q')) | Some(Input::Character('Q')) => {
+                    if input.is_empty() {
+                        // Q pressed with no input = cancel
+                        pancurses::noecho();
+                        pancurses::curs_set(0);
+                        return None;
+                    } else {
+                        // Q is part of input (though invalid as number)
+                        input.push('q');
+                    }
+                }
+                Some(Input::Character(c)) if c.is_ascii_digit() || c == '\x08' || c == '\x7f' => {
+                    // Allow digits and backspace
+                    if c == '\x08' || c == '\x7f' {
+                        // Backspace
+                        input.pop();
+                    } else {
+                        input.push(c);
+                    }
+                }
+                Some(Input::Character(c)) => {
+                    // Allow other characters but they'll cause parse error
+                    input.push(c);
+                }
+                _ => {}
+            }
+        }
+
+        // Disable echo and cursor
+        pancurses::noecho();
+        pancurses::curs_set(0);
+
+        Some(input)
+    }
+
+    /// Show error message for invalid typed input
+    fn show_input_error(&self, message: &str) {
         self.window.clear();
 
-        let color_pair = pancurses::COLOR_PAIR(2);
+        let color_pair = self.color(2);
         self.window.attron(color_pair);
         self.window.mvprintw(2, 2, "ERROR: Invalid Input");
         self.window.attroff(color_pair);
@@ -650,177 +1690,1207 @@ impl Renderer {
         self.window.mvprintw(6, 2, "Press any key to try again...");
 
         self.window.refresh();
-        self.window.getch(); // Wait for key press
+        self.wait_for_key(); // Wait for key press
     }
 
-    fn render(&self, game: &GameState) {
-        self.window.clear();
-
-        // Render top info panel
-        self.render_info_panel(game);
-
-        // Render game area
-        self.render_game_area(game);
+    /// Wait for a key like a blocking getch, but give up (None) once Ctrl+C has been pressed
+    /// so the caller can quit; the window is left blocking
+    fn wait_for_key(&self) -> Option<Input> {
+        self.window.timeout(INTERRUPT_POLL_MS);
+        let input = loop {
+            if interrupted() {
+                break None;
+            }
+            if let Some(input) = self.window.getch() {
+                break Some(input);
+            }
+        };
+        self.window.timeout(-1);
+        input
+    }
 
-        // Render status messages
-        self.render_status_messages(game);
+    /// Overlay a Resume/Restart/Quit menu on the frozen board
+    /// Navigate with arrow keys + Enter or press 1-3; P/Esc resumes
+    fn show_pause_menu(&self, game: &GameState) -> PauseChoice {
+        // Blocking input: the game loop (and clock) stands still while the menu is up
+        self.window.timeout(-1);
 
-        self.window.refresh();
-    }
+        // Board stays visible behind the menu
+        self.render(game);
 
-    fn render_info_panel(&self, game: &GameState) {
-        let color_pair = pancurses::COLOR_PAIR(4);
-        self.window.attron(color_pair);
+        let mut selected = 0;
+        loop {
+            // Box with title and options; the selected option is highlighted
+            let (menu_x, menu_y) = self.draw_overlay_box(game, 18, PAUSE_OPTIONS.len() as i32 + 4, "PAUSED");
 
-        let x = game.offset_x + 1;
-        let y = game.offset_y;
+            for (i, (_, label)) in PAUSE_OPTIONS.iter().enumerate() {
+                let attr = if i == selected {
+                    pancurses::A_REVERSE
+                } else {
+                    self.color(4)
+                };
+                self.window.attron(attr);
+                self.window.mvprintw(menu_y + 3 + i as i32, menu_x + 3, format!("{}. {}", i + 1, label));
+                self.window.attroff(attr);
+            }
+            self.window.refresh();
 
-        self.window.mvprintw(y, x, &format!("=== RUST SNAKE ==="));
-        self.window.mvprintw(y + 1, x, &format!("Score: {}  |  Length: {}  |  Speed: {}ms",
-            game.score, game.snake.len(), game.frame_delay.as_millis()));
-        self.window.mvprintw(y + 2, x, &format!("Controls: Arrow Keys=Move  P=Pause  Q=Quit"));
+            let choice = match self.wait_for_key() {
+                None => Some(PauseChoice::Quit), // Ctrl+C
+                Some(Input::KeyUp) => {
+                    selected = (selected + PAUSE_OPTIONS.len() - 1) % PAUSE_OPTIONS.len();
+                    None
+                }
+                Some(Input::KeyDown) => {
+                    selected = (selected + 1) % PAUSE_OPTIONS.len();
+                    None
+                }
+                Some(Input::Character('\n')) | Some(Input::KeyEnter) => Some(PAUSE_OPTIONS[selected].0),
+                Some(Input::Character(c @ '1'..='3')) => Some(PAUSE_OPTIONS[c as usize - '1' as usize].0),
+                Some(Input::Character('p')) | Some(Input::Character('P')) | Some(Input::Character('\x1b')) => {
+                    Some(PauseChoice::Resume)
+                }
+                Some(Input::Character('q')) | Some(Input::Character('Q')) => Some(PauseChoice::Quit),
+                _ => None,
+            };
 
-        self.window.attroff(color_pair);
+            if let Some(choice) = choice {
+                self.window.timeout(0); // Restore non-blocking for gameplay
+                return choice;
+            }
+        }
     }
 
-    fn render_game_area(&self, game: &GameState) {
-        // Game area starts below info panel (3 lines + 1 blank = 4)
-        let render_offset_y = game.offset_y + 4;
-        let render_offset_x = game.offset_x + 1;
+    /// Draw an empty titled box centered over the field; returns its top-left corner
+    fn draw_overlay_box(&self, game: &GameState, width: i32, height: i32, title: &str) -> (i32, i32) {
+        let (view_width, view_height) = self.view_size(game.game_width, game.game_height);
+        let x = (game.offset_x + 1 + (view_width * self.cell_width() - width) / 2).max(0);
+        let y = (game.offset_y + INFO_PANEL_HEIGHT + 1 + (view_height - height) / 2).max(0);
+
+        let border = format!("+{}+", "-".repeat(width as usize - 2));
+        let blank = format!("|{}|", " ".repeat(width as usize - 2));
+        self.window.attron(self.color(3));
+        self.window.mvprintw(y, x, &border);
+        for row in 1..height - 1 {
+            self.window.mvprintw(y + row, x, &blank);
+        }
+        self.window.mvprintw(y + height - 1, x, &border);
+        self.window.mvprintw(y + 1, x + (width - title.len() as i32) / 2, title);
+        self.window.attroff(self.color(3));
 
-        // Draw border
-        let border_color = pancurses::COLOR_PAIR(3);
-        self.window.attron(border_color);
+        (x, y)
+    }
 
-        // Top and bottom borders
-        for x in 0..=game.game_width + 1 {
-            self.window.mvaddch(render_offset_y - 1, render_offset_x + x, '#');
-            self.window.mvaddch(render_offset_y + game.game_height, render_offset_x + x, '#');
-        }
+    /// Ask for a name to put on the leaderboard (blocking)
+    /// Returns None if the player skips with Esc
+    fn prompt_name(&self, game: &GameState) -> Option<String> {
+        self.window.timeout(-1);
+        self.render(game);
+
+        let (box_x, box_y) = self.draw_overlay_box(game, 30, 7, "NEW TOP 10 SCORE!");
+        self.window.attron(self.color(4));
+        self.window.mvprintw(box_y + 2, box_x + 3, format!("Score: {}", game.score));
+        self.window.mvprintw(box_y + 5, box_x + 3, "Enter=Save  Esc=Skip");
+        self.window.attroff(self.color(4));
+
+        // Echo the name ourselves so only allowed characters ever show up
+        let mut name = String::new();
+        let name_x = box_x + 3 + "Name: ".len() as i32;
+        pancurses::curs_set(1);
+        let result = loop {
+            let field = format!("Name: {:<width$}", name, width = scores::MAX_NAME_LEN);
+            self.window.mvprintw(box_y + 3, box_x + 3, field);
+            self.window.mv(box_y + 3, name_x + name.len() as i32);
+            self.window.refresh();
 
-        // Left and right borders
-        for y in 0..game.game_height {
-            self.window.mvaddch(render_offset_y + y, render_offset_x - 1, '#');
-            self.window.mvaddch(render_offset_y + y, render_offset_x + game.game_width, '#');
-        }
+            match self.wait_for_key() {
+                None => break None, // Ctrl+C
+                Some(Input::Character('\n')) | Some(Input::KeyEnter) => {
+                    let name = name.trim();
+                    break Some(if name.is_empty() { "Anonymous".to_string() } else { name.to_string() });
+                }
+                Some(Input::Character('\x1b')) => break None,
+                Some(Input::KeyBackspace) | Some(Input::Character('\x08')) | Some(Input::Character('\x7f')) => {
+                    name.pop();
+                }
+                Some(Input::Character(c)) if (c.is_ascii_graphic() || c == ' ') && name.len() < scores::MAX_NAME_LEN => {
+                    name.push(c);
+                }
+                _ => {}
+            }
+        };
+        pancurses::curs_set(0);
 
-        self.window.attroff(border_color);
+        self.window.timeout(0); // Restore non-blocking for gameplay
+        result
+    }
 
-        // Draw food
-        let food_color = pancurses::COLOR_PAIR(2);
-        self.window.attron(food_color);
-        self.window.mvaddch(
-            render_offset_y + game.food.y,
-            render_offset_x + game.food.x,
-            '@',
-        );
-        self.window.attroff(food_color);
+    /// Show the leaderboard until a key is pressed, highlighting the entry at `highlight`
+    /// The board is the one for the game's mode, which is named under the entries
+    fn show_leaderboard(&self, game: &GameState, leaderboard: &scores::Leaderboard, highlight: Option<usize>) {
+        self.window.timeout(-1);
+        self.render(game);
 
-        // Draw snake
-        let snake_color = pancurses::COLOR_PAIR(1);
-        self.window.attron(snake_color);
+        let mode = format!("Mode: {}", game.settings.mode_name());
+        let height = scores::MAX_ENTRIES as i32 + 6;
+        let width = (mode.len() as i32 + 6).max(30);
+        let (box_x, box_y) = self.draw_overlay_box(game, width, height, "HIGH SCORES");
+        self.window.mvprintw(box_y + height - 3, box_x + 3, &mode);
 
-        for (i, segment) in game.snake.iter().enumerate() {
-            let ch = if i == 0 { 'O' } else { 'o' }; // Head vs body
-            self.window.mvaddch(
-                render_offset_y + segment.y,
-                render_offset_x + segment.x,
-                ch,
+        if leaderboard.entries().is_empty() {
+            self.window.mvprintw(box_y + 3, box_x + 3, "No scores yet");
+        }
+        for (i, entry) in leaderboard.entries().iter().enumerate() {
+            let attr = if Some(i) == highlight { pancurses::A_REVERSE } else { self.color(4) };
+            self.window.attron(attr);
+            self.window.mvprintw(
+                box_y + 3 + i as i32,
+                box_x + 3,
+                format!("{:>2}. {:<width$} {:>6}", i + 1, entry.name, entry.score, width = scores::MAX_NAME_LEN),
             );
+            self.window.attroff(attr);
         }
+        self.window.mvprintw(box_y + height - 2, box_x + 3, "Press any key");
+        self.window.refresh();
 
-        self.window.attroff(snake_color);
+        self.wait_for_key();
+        self.window.timeout(0); // Restore non-blocking for gameplay
     }
 
-    fn render_status_messages(&self, game: &GameState) {
-        // Position below the game area
-        let msg_y = game.offset_y + 4 + game.game_height + 1;
-        let msg_x = game.offset_x + 1;
+    /// --title: put "Snake: <score>" in the terminal title whenever the score changes
+    fn show_score_in_title(&mut self, score: u32) {
+        if self.title && self.title_score != Some(score) {
+            self.title_score = Some(score);
+            set_terminal_title(&format!("Snake: {}", score));
+        }
+    }
 
-        // Show initial start message (takes priority over other states)
-        if game.waiting_for_start {
-            let color_pair = pancurses::COLOR_PAIR(3);
-            self.window.attron(color_pair);
-            self.window.mvprintw(msg_y, msg_x, "*** Press arrow key to start ***");
-            self.window.attroff(color_pair);
+    fn render(&self, game: &GameState) {
+        self.window.clear();
+
+        // Nothing fits after a resize - only explain how to continue
+        if game.terminal_too_small {
+            self.render_too_small(game);
+            self.window.refresh();
             return;
         }
 
-        match game.status {
-            GameStatus::Paused => {
-                let color_pair = pancurses::COLOR_PAIR(3);
-                self.window.attron(color_pair);
-                self.window.mvprintw(msg_y, msg_x, "*** PAUSED - Press P to continue ***");
-                self.window.attroff(color_pair);
-            }
-            GameStatus::GameOver => {
-                let color_pair = pancurses::COLOR_PAIR(2);
-                self.window.attron(color_pair);
-                self.window.mvprintw(
-                    msg_y,
-                    msg_x,
-                    &format!("*** GAME OVER! Final Score: {} - Press Q to quit or R to restart ***", game.score),
-                );
-                self.window.attroff(color_pair);
-            }
-            GameStatus::Playing => {}
+        // Render top info panel
+        self.render_info_panel(game);
+        if let Some(frame_timer) = &self.frame_timer {
+            self.render_debug(game, frame_timer);
         }
-    }
-}
 
-impl Drop for Renderer {
-    fn drop(&mut self) {
-        // Always cleanup curses on exit
+        // Render game area
+        self.render_game_area(game);
+
+        // Render status messages
+        self.render_status_messages(game);
+
+        // The help overlay goes on top of everything
+        if game.show_help {
+            self.render_help(game);
+        }
+
+        self.window.refresh();
+    }
+
+    /// Box over the field listing every key and the settings of this game
+    fn render_help(&self, game: &GameState) {
+        let keys = &self.keys;
+        let settings = &game.settings;
+        let on_off = |on: bool| if on { "On" } else { "Off" };
+        let move_keys: Vec<String> =
+            [keys.up, keys.left, keys.down, keys.right].iter().map(|&key| KeyBindings::label(key)).collect();
+        let move_help = if settings.versus {
+            format!("P1 Arrows, P2 {}", move_keys.join(" "))
+        } else {
+            format!("Arrows or {}", move_keys.join(" "))
+        };
+
+        let mut lines = vec![format!("Move ........ {}", move_help)];
+        if settings.diagonal {
+            lines.push("Diagonal .... Numpad 7 9 1 3".to_string());
+        }
+        lines.extend([
+            format!("Pause ....... {}", KeyBindings::label(keys.pause)),
+            format!("Quit ........ {}", KeyBindings::label(keys.quit)),
+            if self.manual_step {
+                format!("Step ........ {}", KeyBindings::label(keys.slowmo))
+            } else {
+                format!("Slow-mo ..... {}", KeyBindings::label(keys.slowmo))
+            },
+            format!("Speed ....... {} / {}", KeyBindings::label(keys.faster), KeyBindings::label(keys.slower)),
+            format!("Help ........ {}", KeyBindings::label(keys.help)),
+            format!("Restart ..... {} (after the game), Ctrl+R (any time)", KeyBindings::label(keys.restart)),
+            format!("Scores ...... {} (after the game)", KeyBindings::label(keys.scores)),
+            format!("Menus ....... {} (after the game)", KeyBindings::label(keys.menu)),
+            format!("Seed ........ {} (after the game: same or new on restart)", KeyBindings::label(keys.seed)),
+            format!(
+                "Reroll food . {} (-{} points, {} per game)",
+                KeyBindings::label(keys.reroll),
+                game::REROLL_COST,
+                game::REROLLS_PER_GAME
+            ),
+        ]);
+        if game.rewind_limit > 0 {
+            lines.push(format!("Rewind ...... {} (after a crash)", KeyBindings::label(keys.rewind)));
+        }
+        lines.extend([
+            String::new(),
+            format!(
+                "Walls: {}  Arena: {}  Obstacles: {}",
+                if settings.wrap_walls {
+                    "Wrap"
+                } else if settings.doorways {
+                    "Doorways"
+                } else {
+                    "Solid"
+                },
+                if settings.shrinking { "Shrinking" } else { "Fixed" },
+                match settings.layout {
+                    Layout::Open => "Open",
+                    Layout::Blocks => "Blocks",
+                    Layout::Campaign => "Campaign",
+                }
+            ),
+            format!(
+                "Trail: {}  Poison: {}%  Portals: {}  Near misses: {}",
+                on_off(settings.hazard_trail),
+                settings.poison_chance,
+                on_off(settings.portals),
+                on_off(settings.near_miss)
+            ),
+            format!(
+                "Tron: {}  Growth: +{}  Hunger: {}  Forgiven: {}  Assist: {}",
+                on_off(settings.tron),
+                settings.growth_per_food,
+                settings.hunger_limit,
+                settings.self_collision_forgiveness,
+                on_off(settings.assist)
+            ),
+            format!(
+                "Start length: {}  Max length: {}  Fair food: {}  Frames: {}-{} ms",
+                settings.start_length,
+                match settings.max_length {
+                    0 => "None".to_string(),
+                    max => max.to_string(),
+                },
+                on_off(settings.fair_spawn),
+                settings.min_frame_ms,
+                settings.max_frame_ms
+            ),
+            format!(
+                "Speed-up: {}  Combos: {}  Chaos: {}  Valued food: {}  Time limit: {}",
+                match settings.speed_curve {
+                    SpeedCurve::Score => "By score",
+                    SpeedCurve::Length => "By length",
+                },
+                on_off(settings.combos),
+                on_off(settings.chaos),
+                on_off(settings.valued_food),
+                match settings.time_limit {
+                    Some(limit) => format!("{}s", limit.as_secs()),
+                    None => "None".to_string(),
+                }
+            ),
+        ]);
+        let footer = format!("{} or a direction key closes this", KeyBindings::label(keys.help));
+
+        let width = lines.iter().chain([&footer]).map(|line| line.len()).max().unwrap_or(0) as i32 + 6;
+        let (box_x, box_y) = self.draw_overlay_box(game, width, lines.len() as i32 + 6, "HELP");
+        self.window.attron(self.color(4));
+        for (i, line) in lines.iter().enumerate() {
+            self.window.mvprintw(box_y + 3 + i as i32, box_x + 3, line);
+        }
+        self.window.mvprintw(box_y + 4 + lines.len() as i32, box_x + 3, &footer);
+        self.window.attroff(self.color(4));
+    }
+
+    /// Draw "3", "2", "1", then "GO!" on the given row, centered under the field
+    /// (not in the middle of it, which is where the snake starts)
+    fn render_countdown(&self, game: &GameState, y: i32, remaining: u8) {
+        let text = if remaining == 0 { "GO!".to_string() } else { remaining.to_string() };
+        let view_width = self.view_size(game.game_width, game.game_height).0;
+        let x = game.offset_x + 1 + (view_width * self.cell_width() - text.len() as i32) / 2;
+
+        let attr = self.color(self.theme.highlight_pair) | pancurses::A_BOLD;
+        self.window.attron(attr);
+        self.window.mvprintw(y, x, &text);
+        self.window.attroff(attr);
+    }
+
+    fn render_too_small(&self, game: &GameState) {
+        let color_pair = self.color(self.theme.alert_pair);
+        self.window.attron(color_pair);
+        self.window.mvprintw(0, 0, "Terminal too small for this field!");
+        self.window.attroff(color_pair);
+
+        let (required_width, required_height) = self.required_size(game.game_width, game.game_height);
+        self.window.mvprintw(
+            1,
+            0,
+            format!(
+                "Need {}x{}, have {}x{}",
+                required_width,
+                required_height,
+                self.window.get_max_x(),
+                self.window.get_max_y()
+            ),
+        );
+        self.window.mvprintw(
+            2,
+            0,
+            format!(
+                "Enlarge the terminal to continue, or press {} to quit",
+                KeyBindings::label(self.keys.quit)
+            ),
+        );
+    }
+
+    /// --debug: frame number and FPS, then the heads, length, collision bounds, and food
+    /// as the game logic sees them, right-aligned in the top rows
+    fn render_debug(&self, game: &GameState, frame_timer: &FrameTimer) {
+        let cell = |point: &Point| format!("({},{})", point.x, point.y);
+        let heads: Vec<String> = game.snakes.iter().map(|snake| cell(&snake.head())).collect();
+        let lengths: Vec<String> = game.snakes.iter().map(|snake| snake.body().len().to_string()).collect();
+        let bounds = match collision_bounds(game) {
+            Some((first, last)) => format!("x {}-{} y {}-{}", first.x, last.x, first.y, last.y),
+            None => "none".to_string(),
+        };
+        let food: Vec<String> = game.food.iter().map(cell).collect();
+
+        let lines = [
+            format!("Frame {}  FPS {:.1}", game.frame, frame_timer.fps()),
+            format!("Head {}  Length {}", heads.join("/"), lengths.join("/")),
+            format!("Bounds {}{}", bounds, if game.settings.wrap_walls { " (wrap)" } else { "" }),
+            format!("Food {}", food.join(" ")),
+        ];
+        self.window.attron(pancurses::A_DIM);
+        for (y, text) in lines.iter().enumerate() {
+            let x = (self.window.get_max_x() - text.len() as i32).max(0);
+            self.window.mvprintw(y as i32, x, text);
+        }
+        self.window.attroff(pancurses::A_DIM);
+    }
+
+    fn render_info_panel(&self, game: &GameState) {
+        let color_pair = self.color(self.theme.text_pair);
+        self.window.attron(color_pair);
+
+        let x = game.offset_x + 1;
+        let y = game.offset_y;
+
+        // Title line doubles as a summary of the active rules
+        let rules = if game.settings.self_collision_forgiveness > 0 {
+            format!("Rules: Forgiving ({} left)", game.self_collision_forgiveness)
+        } else {
+            "Rules: Classic".to_string()
+        };
+        let rules = if game.settings.layout == Layout::Campaign {
+            let level = &levels::LEVELS[game.level];
+            format!(
+                "{}  |  Level {}/{}: {} (goal {})",
+                rules,
+                game.level + 1,
+                levels::LEVELS.len(),
+                level.name,
+                level.goal
+            )
+        } else {
+            rules
+        };
+        // Growth is only mentioned when it isn't the classic one segment per food
+        let growth = if game.settings.growth_per_food > 1 {
+            format!("  |  Growth: +{}", game.settings.growth_per_food)
+        } else {
+            String::new()
+        };
+        // Time attack counts down what's left (rounded up, so 00:00 only shows once time is up);
+        // the clock comes first so a long rules summary is what gets cut off
+        let (clock, seconds) = match game.time_left() {
+            Some(left) => ("Time left", (left.as_millis() as u64).div_ceil(1000)),
+            None => ("Time", game.elapsed().as_secs()),
+        };
+        self.print_clipped(
+            y,
+            x,
+            &format!(
+                "=== RUST SNAKE ===  {}: {:02}:{:02}  |  {}{}",
+                clock,
+                seconds / 60,
+                seconds % 60,
+                rules,
+                growth
+            ),
+        );
+        // Hunger mode: moves left before each snake loses a segment
+        let hunger = if game.settings.hunger_limit == 0 {
+            String::new()
+        } else {
+            let left: Vec<String> = game.snakes.iter().map(|snake| snake.hunger.to_string()).collect();
+            format!("  |  Hunger: {}", left.join("/"))
+        };
+        // Combos: what the next food is worth if it comes in time
+        let combo = if game.settings.combos {
+            format!("  |  Combo x{:.1}", game.combo_multiplier())
+        } else {
+            String::new()
+        };
+        // Near misses: how many times the heads have brushed past their own bodies
+        let style = if game.settings.near_miss { format!("  |  Style: {}", game.style) } else { String::new() };
+        // Food rerolls still to be had this game, once the first one is used (the help lists
+        // how many a game starts with)
+        let rerolls = if game.rerolls_left < game::REROLLS_PER_GAME {
+            format!("  |  Rerolls: {} left", game.rerolls_left)
+        } else {
+            String::new()
+        };
+        // Chaos mode: warn while the reverse powerup has the direction keys turned around
+        let reversed = if game.controls_inverted() { "  |  CONTROLS REVERSED" } else { "" };
+        // A snake that has reached the length cap shows MAX next to its length
+        let length = |player: usize| {
+            let len = game.snakes[player].body().len();
+            if game.at_max_length(player) { format!("{} MAX", len) } else { len.to_string() }
+        };
+        // Said when the speed follows the length, so the link between the two is plain to see
+        let speed = match game.settings.speed_curve {
+            SpeedCurve::Score => format!("{}ms", game.frame_duration.as_millis()),
+            SpeedCurve::Length => format!("{}ms by length", game.frame_duration.as_millis()),
+        };
+        if game.settings.versus {
+            self.print_clipped(y + 1, x, &format!("P1 Length: {}  |  P2 Length: {}  |  Speed: {}",
+                length(0), length(1), speed));
+        } else {
+            // How much of the board the snake covers, toward the fill-the-board win
+            let fill = game.snake().body().len() as f32 / (game.game_width * game.game_height) as f32;
+            self.print_clipped(y + 1, x, &format!("Score: {}  |  Length: {}  |  Speed: {}  |  {} {:.0}%",
+                game.score, length(0), speed, fill_bar(fill, FILL_BAR_WIDTH), fill * 100.0));
+            // Flash the score right after eating
+            if game.eat_flash_frames > 0 {
+                let flash_attr = self.color(self.theme.highlight_pair) | pancurses::A_BOLD;
+                self.window.attron(flash_attr);
+                self.window.mvprintw(y + 1, x, format!("Score: {}", game.score));
+                self.window.attroff(flash_attr);
+                self.window.attron(color_pair);
+            }
+        }
+        let keys = &self.keys;
+        let move_keys: String = [keys.up, keys.left, keys.down, keys.right]
+            .iter()
+            .map(|&key| KeyBindings::label(key))
+            .collect();
+        let move_help = if game.settings.versus {
+            format!("P1 Arrows, P2 {}", move_keys)
+        } else {
+            format!("Arrows/{}", move_keys)
+        };
+        // Hardcore runs can't pause (or open the help, which pauses), so those hints are left out
+        let pause_help = if game.settings.allow_pause {
+            format!(
+                "{}=Pause  {}=Quit  {}=Help",
+                KeyBindings::label(keys.pause),
+                KeyBindings::label(keys.quit),
+                KeyBindings::label(keys.help)
+            )
+        } else {
+            format!("{}=Quit", KeyBindings::label(keys.quit))
+        };
+        // Turn-based play has no real time to speed up or slow down
+        let timing_help = if self.manual_step {
+            format!("{}=Step", KeyBindings::label(keys.slowmo))
+        } else {
+            format!(
+                "{}/{}=Speed  |  {}=Slow-mo {}",
+                KeyBindings::label(keys.faster),
+                KeyBindings::label(keys.slower),
+                KeyBindings::label(keys.slowmo),
+                fill_bar(game.slowmo_budget as f32 / game::SLOWMO_MAX_BUDGET as f32, FILL_BAR_WIDTH)
+            )
+        };
+        // The speed keys share a row with the modes' counters, leaving the controls row short
+        self.print_clipped(y + 2, x, &format!("{}{}{}{}{}{}", timing_help, rerolls, combo, style, hunger, reversed));
+        self.print_clipped(y + 3, x, &format!("Controls: {}=Move  {}", move_help, pause_help));
+
+        self.window.attroff(color_pair);
+    }
+
+    fn render_game_area(&self, game: &GameState) {
+        // Game area starts below info panel and the top border, shifted so the camera's
+        // cell comes first (--scroll)
+        let (view_width, view_height) = self.view_size(game.game_width, game.game_height);
+        let (camera_x, camera_y) = self.camera(game);
+        let render_offset_y = game.offset_y + INFO_PANEL_HEIGHT + 1 - camera_y;
+        let render_offset_x = game.offset_x + 1 - camera_x * self.cell_width();
+        // Screen column of a board column (--wide cells are two columns each)
+        let column = |x: i32| render_offset_x + x * self.cell_width();
+
+        // Draw border
+        let border_color = self.color(self.theme.border_pair);
+        self.window.attron(border_color);
+
+        // Walls sit just outside the arena, which shrinks inward in the shrinking mode; where
+        // a wall is out of view (--scroll), a dotted edge shows the field carries on
+        let (arena_width, arena_height) = game.arena_size();
+        let left = (game.inset - 1).max(camera_x - 1);
+        let right = (game.inset + arena_width).min(camera_x + view_width);
+        let top = (game.inset - 1).max(camera_y - 1);
+        let bottom = (game.inset + arena_height).min(camera_y + view_height);
+        let (left_wall, right_wall) = (left == game.inset - 1, right == game.inset + arena_width);
+        let (top_wall, bottom_wall) = (top == game.inset - 1, bottom == game.inset + arena_height);
+        // Box-drawing lines with proper corners where the terminal can show them, the theme's
+        // border character everywhere otherwise
+        let border = self.theme.border.to_string();
+        let line = |unicode: &'static str| if self.box_border { unicode } else { border.as_str() };
+        let edge = |is_wall: bool, unicode: &'static str| if is_wall { line(unicode) } else { ":" };
+        let (left_edge, right_edge) = (edge(left_wall, "│"), edge(right_wall, "│"));
+        let (top_edge, bottom_edge) = (edge(top_wall, "─"), edge(bottom_wall, "─"));
+
+        // Fog of war (--fog): only cells near a head are drawn, until the game is over;
+        // the walls always show so the field keeps its outline. Nothing off screen is drawn.
+        let in_view = |cell: &Point| {
+            (camera_x..camera_x + view_width).contains(&cell.x) && (camera_y..camera_y + view_height).contains(&cell.y)
+        };
+        let in_sight = |cell: &Point| match self.fog {
+            _ if !in_view(cell) => false,
+            Some(radius) if !game.is_finished() => game.snakes.iter().any(|snake| {
+                let head = snake.head();
+                (head.x - cell.x).abs().max((head.y - cell.y).abs()) <= radius
+            }),
+            _ => true,
+        };
+
+        // Doorways are left open as gaps in the wall
+        let doorways = game.doorways();
+        let is_doorway = |x: i32, y: i32| doorways.contains(&Point { x, y });
+
+        // Top and bottom borders (the side walls hug the arena, however wide its cells are)
+        let (left_x, right_x) = (column(left + 1) - 1, column(right));
+        for x in left_x + 1..right_x {
+            let cell_x = left + 1 + (x - column(left + 1)).div_euclid(self.cell_width());
+            if !is_doorway(cell_x, top) {
+                self.window.mvaddstr(render_offset_y + top, x, top_edge);
+            }
+            if !is_doorway(cell_x, bottom) {
+                self.window.mvaddstr(render_offset_y + bottom, x, bottom_edge);
+            }
+        }
+
+        // Corners join the walls on either side (a scrolled-off wall leaves a dotted corner)
+        self.window.mvaddstr(render_offset_y + top, left_x, edge(top_wall && left_wall, "┌"));
+        self.window.mvaddstr(render_offset_y + top, right_x, edge(top_wall && right_wall, "┐"));
+        self.window.mvaddstr(render_offset_y + bottom, left_x, edge(bottom_wall && left_wall, "└"));
+        self.window.mvaddstr(render_offset_y + bottom, right_x, edge(bottom_wall && right_wall, "┘"));
+
+        // Left and right borders
+        for y in top + 1..bottom {
+            if !is_doorway(left, y) {
+                self.window.mvaddstr(render_offset_y + y, left_x, left_edge);
+            }
+            if !is_doorway(right, y) {
+                self.window.mvaddstr(render_offset_y + y, right_x, right_edge);
+            }
+        }
+
+        self.window.attroff(border_color);
+
+        // --debug: mark the corners of the area the collision checks treat as inside, to
+        // compare with the walls drawn around it
+        if self.frame_timer.is_some() {
+            if let Some((first, last)) = collision_bounds(game) {
+                let marker_attr = self.color(self.theme.alert_pair) | pancurses::A_DIM;
+                self.window.attron(marker_attr);
+                for (x, y) in [(first.x, first.y), (last.x, first.y), (first.x, last.y), (last.x, last.y)] {
+                    if in_view(&Point { x, y }) {
+                        self.window.mvaddch(render_offset_y + y, column(x), '+');
+                    }
+                }
+                self.window.attroff(marker_attr);
+            }
+        }
+
+        // Faint checkerboard inside the walls to help judge distances (--grid); everything
+        // else is drawn over it
+        if self.grid {
+            let grid_attr = self.color(self.theme.text_pair) | pancurses::A_DIM;
+            self.window.attron(grid_attr);
+            for y in top + 1..bottom {
+                for x in (left + 1..right).filter(|&x| (x + y) % 2 == 0 && in_sight(&Point { x, y })) {
+                    self.window.mvaddch(render_offset_y + y, column(x), '.');
+                }
+            }
+            self.window.attroff(grid_attr);
+        }
+
+        // Vision cone (--vision): the cell straight ahead of each head and the two diagonally
+        // ahead, dimly tinted; drawn early so whatever is on them covers the tint
+        if self.vision && !game.is_finished() {
+            let cone_attr = self.color(self.theme.highlight_pair) | pancurses::A_DIM;
+            self.window.attron(cone_attr);
+            for snake in game.snakes.iter().filter(|snake| snake.alive) {
+                let (dx, dy) = snake.direction.offset();
+                let ahead = Direction::ALL.iter().filter(|direction| {
+                    let (x, y) = direction.offset();
+                    (x - dx).abs() + (y - dy).abs() <= 1
+                });
+                for &direction in ahead {
+                    let cell = game.step(snake.head(), direction);
+                    if game.in_arena(&cell) && in_sight(&cell) {
+                        self.window.mvaddch(render_offset_y + cell.y, column(cell.x), '.');
+                    }
+                }
+            }
+            self.window.attroff(cone_attr);
+        }
+
+        // Afterimage (--afterimage): the cells the heads just left, fading with age; drawn
+        // beneath everything else and only where no snake is now
+        if self.afterimage {
+            let glyph = if self.box_border { "•" } else { "." };
+            for trail in &game.head_trails {
+                for (cell, &attr) in trail.iter().zip(&self.afterimage_attrs) {
+                    let covered = game.snakes.iter().any(|snake| snake.contains(cell));
+                    if !covered && game.in_arena(cell) && in_sight(cell) {
+                        self.window.attron(attr);
+                        self.window.mvaddstr(render_offset_y + cell.y, column(cell.x), glyph);
+                        self.window.attroff(attr);
+                    }
+                }
+            }
+        }
+
+        // Before the first key press, point the way each snake will go; drawn early so
+        // food or walls in that cell stay visible
+        if game.waiting_for_start {
+            let arrow_attr = self.color(self.theme.highlight_pair) | pancurses::A_DIM;
+            self.window.attron(arrow_attr);
+            for snake in &game.snakes {
+                let next = game.step(snake.head(), snake.direction);
+                if game.in_arena(&next) && in_view(&next) {
+                    self.window.mvaddstr(
+                        render_offset_y + next.y,
+                        column(next.x),
+                        ASCII_SNAKE.head(snake.direction),
+                    );
+                }
+            }
+            self.window.attroff(arrow_attr);
+        }
+
+        // Draw obstacles
+        let obstacle_color = self.color(self.theme.obstacle_pair);
+        self.window.attron(obstacle_color);
+        for obstacle in game.obstacles.iter().filter(|obstacle| game.in_arena(obstacle) && in_sight(obstacle)) {
+            // Solid blocks fill every column of a wide cell
+            for x in column(obstacle.x)..column(obstacle.x + 1) {
+                self.window.mvaddch(render_offset_y + obstacle.y, x, self.theme.obstacle);
+            }
+        }
+        self.window.attroff(obstacle_color);
+
+        // Draw hazards left behind by eaten food (trail mode)
+        let hazard_attr = self.color(self.theme.alert_pair) | pancurses::A_DIM;
+        self.window.attron(hazard_attr);
+        for hazard in game.hazards.iter().filter(|hazard| game.in_arena(hazard) && in_sight(hazard)) {
+            self.window.mvaddch(render_offset_y + hazard.y, column(hazard.x), self.theme.hazard);
+        }
+        self.window.attroff(hazard_attr);
+
+        // Draw the walls left behind by the tails (tron mode)
+        let tron_color = self.color(self.theme.tron_pair);
+        self.window.attron(tron_color);
+        for wall in game.tron_walls.iter().filter(|wall| game.in_arena(wall) && in_sight(wall)) {
+            for x in column(wall.x)..column(wall.x + 1) {
+                self.window.mvaddch(render_offset_y + wall.y, x, self.theme.tron_wall);
+            }
+        }
+        self.window.attroff(tron_color);
+
+        // Draw portals
+        if let Some((a, b)) = game.portals {
+            let portal_color = self.color(self.theme.portal_pair) | pancurses::A_BOLD;
+            self.window.attron(portal_color);
+            for portal in [a, b].iter().filter(|portal| in_sight(portal)) {
+                self.window.mvaddch(render_offset_y + portal.y, column(portal.x), self.theme.portal);
+            }
+            self.window.attroff(portal_color);
+        }
+
+        // While paused the snakes and food are dimmed (and nothing blinks) so the board reads as frozen
+        let paused = game.status == GameStatus::Paused;
+        let (normal, bold, blink) = if paused {
+            (pancurses::A_DIM, pancurses::A_DIM, pancurses::A_NORMAL)
+        } else {
+            (pancurses::A_NORMAL, pancurses::A_BOLD, pancurses::A_BLINK)
+        };
+
+        // Draw food
+        let food_color = self.color(self.theme.food_pair) | normal;
+        self.window.attron(food_color);
+        for food in game.food.iter().filter(|food| in_sight(food)) {
+            // Valued food shows its points as a digit in place of the food glyph
+            let glyph = match game.food_values.get(food) {
+                Some(&value) => char::from_digit(value, 10).unwrap_or(self.theme.food),
+                None => self.theme.food,
+            };
+            self.window.mvaddch(render_offset_y + food.y, column(food.x), glyph);
+        }
+        self.window.attroff(food_color);
+
+        // Draw bonus food (blinking so it stands out while it lasts)
+        if let Some((bonus, _)) = game.bonus.filter(|(bonus, _)| in_sight(bonus)) {
+            let bonus_attr = self.color(self.theme.highlight_pair) | bold | blink;
+            self.window.attron(bonus_attr);
+            self.window.mvaddch(render_offset_y + bonus.y, column(bonus.x), self.theme.bonus);
+            self.window.attroff(bonus_attr);
+        }
+
+        // Draw poison food
+        if let Some((poison, _)) = game.poison.filter(|(poison, _)| in_sight(poison)) {
+            let poison_attr = self.color(self.theme.poison_pair) | bold;
+            self.window.attron(poison_attr);
+            self.window.mvaddch(render_offset_y + poison.y, column(poison.x), self.theme.poison);
+            self.window.attroff(poison_attr);
+        }
+
+        // Draw the reverse-controls powerup (chaos mode)
+        if let Some((reverser, _)) = game.reverser.filter(|(reverser, _)| in_sight(reverser)) {
+            let reverser_attr = self.color(self.theme.highlight_pair) | bold | pancurses::A_REVERSE;
+            self.window.attron(reverser_attr);
+            self.window.mvaddch(render_offset_y + reverser.y, column(reverser.x), self.theme.reverser);
+            self.window.attroff(reverser_attr);
+        }
+
+        // Draw snakes, each player in their own color (a solo snake may cycle through the rainbow
+        // or fade toward its tail); a snake about to starve turns to the warning color
+        let rainbow = self.rainbow && !game.settings.versus;
+        let fade = self.fade && !game.settings.versus;
+        for (player, snake) in game.snakes.iter().enumerate() {
+            // While dying, a dead snake flashes and collapses from the tail toward its head
+            let dying = match game.status {
+                GameStatus::Dying { frames_left } if !snake.alive => Some(frames_left),
+                _ => None,
+            };
+            let visible = match dying {
+                Some(frames_left) => {
+                    let total = game.death_frames.max(frames_left) as usize;
+                    let gone = snake.body().len() * (total - frames_left as usize) / total;
+                    (snake.body().len() - gone).max(1)
+                }
+                None => snake.body().len(),
+            };
+            let flash = dying.is_some_and(|frames_left| frames_left % 2 == 1);
+
+            let hungry = game.is_hungry(player);
+            let pair = match player {
+                _ if hungry || flash => self.theme.alert_pair,
+                0 => self.theme.snake_pair,
+                _ => self.theme.player2_pair,
+            };
+            let snake_color = self.color(pair);
+            let glyphs = if player == 0 { self.snake_glyphs } else { self.player2_glyphs };
+
+            for (i, segment) in snake.body().iter().take(visible).enumerate() {
+                if !in_sight(segment) {
+                    continue;
+                }
+                // Colors flow from head to tail, shifting by one with every move; the head
+                // lights up for a moment after eating
+                let segment_color = if i == 0 && game.eat_flash_frames > 0 && snake.alive {
+                    self.color(self.theme.highlight_pair) | pancurses::A_BOLD
+                } else if rainbow && !hungry && !flash {
+                    let index = (i as u64 + game.frame) % RAINBOW_PAIRS.len() as u64;
+                    self.color(RAINBOW_PAIRS[index as usize])
+                } else if fade && !hungry && !flash {
+                    // The body is split into equal bands, one shade each from head to tail
+                    let shade = i * FADE_PAIRS.len() / snake.body().len();
+                    if self.fade_pairs {
+                        self.color(FADE_PAIRS[shade])
+                    } else if shade % 2 == 1 {
+                        snake_color
+                    } else {
+                        snake_color | pancurses::A_BOLD
+                    }
+                } else {
+                    snake_color
+                };
+
+                // Look at the neighbours toward the head and toward the tail
+                let toward = |neighbour: Option<&Point>| {
+                    neighbour.and_then(|n| segment.direction_to(n, arena_width, arena_height))
+                };
+                let to_head = if i > 0 { toward(snake.body().get(i - 1)) } else { None };
+                let to_tail = toward(snake.body().get(i + 1));
+
+                let glyph = match (i, to_head, to_tail) {
+                    (0, _, _) => glyphs.head(snake.direction),
+                    (_, Some(a), Some(b)) => glyphs.body(a, b),
+                    // Tail end: straight piece continuing toward its neighbour
+                    (_, Some(a), None) | (_, None, Some(a)) => glyphs.body(a, a),
+                    (_, None, None) => glyphs.body(snake.direction, snake.direction),
+                };
+                let segment_color = segment_color | normal;
+                self.window.attron(segment_color);
+                self.window.mvaddstr(
+                    render_offset_y + segment.y,
+                    column(segment.x),
+                    glyph,
+                );
+                // Wide cells: bridge the gap to a neighbour on the right so the body stays joined
+                if self.wide && (to_head == Some(Direction::Right) || to_tail == Some(Direction::Right)) {
+                    self.window.mvaddstr(
+                        render_offset_y + segment.y,
+                        column(segment.x) + 1,
+                        glyphs.horizontal,
+                    );
+                }
+                self.window.attroff(segment_color);
+            }
+        }
+
+        // Assist: light up whatever sits where a deadly next move would go (a wall, an obstacle,
+        // or a snake), keeping its glyph
+        if game.settings.assist && !game.is_finished() {
+            let pair = if self.has_color { self.theme.alert_pair as i16 } else { 0 };
+            for player in 0..game.snakes.len() {
+                let Some(cell) = game.danger_ahead(player) else { continue };
+                if (left..=right).contains(&cell.x) && (top..=bottom).contains(&cell.y) {
+                    let attributes = pancurses::A_REVERSE | pancurses::A_BOLD;
+                    self.window.mvchgat(render_offset_y + cell.y, column(cell.x), self.cell_width(), attributes, pair);
+                }
+            }
+        }
+    }
+
+    fn render_status_messages(&self, game: &GameState) {
+        // Position below the game area
+        let msg_y = game.offset_y + INFO_PANEL_HEIGHT + 1 + self.view_size(game.game_width, game.game_height).1 + 1;
+        let msg_x = game.offset_x + 1;
+
+        // A pending quit question takes priority over everything else
+        if game.confirm_quit {
+            let color_pair = self.color(self.theme.alert_pair);
+            self.window.attron(color_pair);
+            self.window.mvprintw(msg_y, msg_x, "*** Quit? (y/n) ***");
+            self.window.attroff(color_pair);
+            return;
+        }
+
+        // Show initial start message (takes priority over other states)
+        if game.waiting_for_start {
+            let color_pair = self.color(self.theme.highlight_pair);
+            self.window.attron(color_pair);
+            self.window.mvprintw(msg_y, msg_x, "*** Press a direction key to start ***");
+            self.window.attroff(color_pair);
+            return;
+        }
+
+        match game.status {
+            GameStatus::Paused => {
+                let color_pair = self.color(self.theme.highlight_pair);
+                self.window.attron(color_pair);
+                let label = KeyBindings::label(self.keys.pause);
+                let message = if game.auto_paused {
+                    format!("*** AUTO-PAUSED - Press {} or a direction to continue ***", label)
+                } else {
+                    format!("*** PAUSED - Press {} to continue ***", label)
+                };
+                self.window.mvprintw(msg_y, msg_x, message);
+                self.window.attroff(color_pair);
+            }
+            GameStatus::GameOver | GameStatus::Won => {
+                let theme = self.theme;
+                let (headline, pair) = match (game.settings.versus, game.winner) {
+                    (true, Some(player)) => (format!("PLAYER {} WINS!", player + 1), theme.snake_pair),
+                    (true, None) => ("DRAW!".to_string(), theme.highlight_pair),
+                    _ if game.status == GameStatus::Won => ("YOU WIN!".to_string(), theme.snake_pair),
+                    _ if game.time_up() => ("TIME'S UP!".to_string(), theme.highlight_pair),
+                    _ => ("GAME OVER!".to_string(), theme.alert_pair),
+                };
+                // Each line has to fit one row of an 80-column terminal
+                let color_pair = self.color(pair);
+                self.window.attron(color_pair);
+                self.print_clipped(
+                    msg_y,
+                    msg_x,
+                    &format!(
+                        "*** {} Final Score: {} ***  {}=Quit  {}=Restart  {}=Menus",
+                        headline,
+                        game.score,
+                        KeyBindings::label(self.keys.quit),
+                        KeyBindings::label(self.keys.restart),
+                        KeyBindings::label(self.keys.menu)
+                    ),
+                );
+                self.window.attroff(color_pair);
+
+                // Efficiency for speedrunners: points per second of actual play
+                let play_seconds = game.elapsed().as_secs_f64();
+                let points_per_second = if play_seconds > 0.0 { game.score as f64 / play_seconds } else { 0.0 };
+                // Session best: top score since the program started (never saved)
+                let details = format!("Session best: {}  |  {:.2} points/s", self.session_best, points_per_second);
+
+                if game.new_high_score {
+                    let highlight = self.color(self.theme.highlight_pair);
+                    self.window.attron(highlight);
+                    self.print_clipped(msg_y + 1, msg_x, &format!("*** NEW HIGH SCORE! ***  |  {}", details));
+                    self.window.attroff(highlight);
+                } else {
+                    // The mode name can get long, so it goes last
+                    self.print_clipped(
+                        msg_y + 1,
+                        msg_x,
+                        &format!("High score: {}  |  {}  |  Mode: {}", game.high_score, details, game.settings.mode_name()),
+                    );
+                }
+
+                // Seed lets the player replay the same food sequence with --seed, or on
+                // restarts while the seed is kept
+                let seed_mode = if game.fixed_seed { "same" } else { "new" };
+                let rewind = if game.rewind_limit > 0 && game.status == GameStatus::GameOver {
+                    format!("  {}=Rewind", KeyBindings::label(self.keys.rewind))
+                } else {
+                    String::new()
+                };
+                self.print_clipped(
+                    msg_y + 2,
+                    msg_x,
+                    &format!(
+                        "Seed: {} ({} on restart)  {}=Switch  {}=Top 10{}",
+                        game.seed,
+                        seed_mode,
+                        KeyBindings::label(self.keys.seed),
+                        KeyBindings::label(self.keys.scores),
+                        rewind
+                    ),
+                );
+            }
+            // The pre-start countdown, here below the field so it never covers the snake
+            GameStatus::Playing => {
+                if let Some(remaining) = game.countdown {
+                    self.render_countdown(game, msg_y, remaining);
+                }
+            }
+            GameStatus::Dying { .. } => {}
+        }
+    }
+}
+
+impl Drop for Renderer {
+    fn drop(&mut self) {
+        // Always cleanup curses on exit
         endwin();
+
+        // Don't leave a stale score in the title once the game is gone
+        if self.title_score.is_some() {
+            set_terminal_title("");
+        }
     }
 }
 
+/// --debug: first and last board cell (top-left and bottom-right) that `in_arena` accepts,
+/// found by asking the game logic itself rather than the renderer's idea of the walls
+fn collision_bounds(game: &GameState) -> Option<(Point, Point)> {
+    let inside = (0..game.game_height)
+        .flat_map(|y| (0..game.game_width).map(move |x| Point { x, y }))
+        .filter(|cell| game.in_arena(cell));
+    inside.fold(None, |bounds, cell| match bounds {
+        None => Some((cell, cell)),
+        Some((first, last)) => Some((
+            Point { x: first.x.min(cell.x), y: first.y.min(cell.y) },
+            Point { x: last.x.max(cell.x), y: last.y.max(cell.y) },
+        )),
+    })
+}
+
+/// Set the terminal's window/tab title (OSC 0; terminals without support ignore it)
+/// Written straight to stdout after curses has drawn, so it doesn't disturb the screen
+fn set_terminal_title(title: &str) {
+    let mut stdout = std::io::stdout();
+    let _ = write!(stdout, "\x1b]0;{}\x07", title);
+    let _ = stdout.flush();
+}
+
 // ============================================================================
 // INPUT HANDLING
 // ============================================================================
 
-fn handle_input(window: &Window, game: &mut GameState) -> bool {
-    match window.getch() {
-        Some(Input::Character('q')) | Some(Input::Character('Q')) => {
-            return false; // Quit game
+/// What the game loop should do after the player's input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputOutcome {
+    Continue,
+    Step, // --step: move the snakes on by one cell
+    Quit,
+    Menu, // Back to the menus to set up a different game
+    Reroll, // Move the food elsewhere (the caller records it for replays)
+    Restart, // The game was started over (which ends the recorded game)
+}
+
+/// Process everything typed since the last frame
+/// Quick successive turns each get their own move: the snakes queue them up
+fn handle_input(renderer: &Renderer, game: &mut GameState) -> InputOutcome {
+    while let Some(input) = renderer.window.getch() {
+        game.frames_since_input = 0;
+        let outcome = handle_key(renderer, game, input);
+        if outcome != InputOutcome::Continue {
+            return outcome;
+        }
+    }
+
+    InputOutcome::Continue
+}
+
+/// Apply a single key press
+fn handle_key(renderer: &Renderer, game: &mut GameState, input: Input) -> InputOutcome {
+    if input == Input::KeyResize {
+        // Pick up the new terminal dimensions, then re-center the field
+        pancurses::resize_term(0, 0);
+        let (offset_x, offset_y) = renderer.calculate_offsets(game.game_width, game.game_height);
+        let fits = renderer.field_fits(game.game_width, game.game_height);
+        game.relocate(offset_x, offset_y, fits);
+        return InputOutcome::Continue;
+    }
+
+    // Any key skips the death animation (and does nothing else, so a held arrow can't restart)
+    if matches!(game.status, GameStatus::Dying { .. }) {
+        game.skip_death_animation();
+        return InputOutcome::Continue;
+    }
+
+    // Answering "Quit? (y/n)": only y quits, any other key carries on
+    if game.confirm_quit {
+        game.confirm_quit = false;
+        if matches!(input, Input::Character('y' | 'Y')) {
+            return InputOutcome::Quit;
+        }
+        game.toggle_pause();
+        return InputOutcome::Continue;
+    }
+
+    // Diagonal mode: numpad corners (with or without Num Lock) move diagonally
+    let numpad = match input {
+        _ if !game.settings.diagonal => None,
+        Input::KeyA1 | Input::KeyHome | Input::Character('7') => Some(Direction::UpLeft),
+        Input::KeyA3 | Input::KeyPPage | Input::Character('9') => Some(Direction::UpRight),
+        Input::KeyC1 | Input::KeyEnd | Input::Character('1') => Some(Direction::DownLeft),
+        Input::KeyC3 | Input::KeyNPage | Input::Character('3') => Some(Direction::DownRight),
+        _ => None,
+    };
+
+    // Arrow keys always move; character keys follow the configured bindings
+    let action = match input {
+        Input::KeyUp => Some(Action::Move(Direction::Up)),
+        Input::KeyDown => Some(Action::Move(Direction::Down)),
+        Input::KeyLeft => Some(Action::Move(Direction::Left)),
+        Input::KeyRight => Some(Action::Move(Direction::Right)),
+        _ if numpad.is_some() => numpad.map(Action::Move),
+        Input::Character(c) => renderer.keys.action_for(c),
+        _ => None,
+    };
+
+    // Ctrl+R: start over at once, mid-game included, with the same settings (but not while
+    // the field doesn't fit, like every other key but quit)
+    if input == Input::Character(CTRL_R) && !game.terminal_too_small {
+        game.restart();
+        return InputOutcome::Restart;
+    }
+
+    // While the help is up, only its key or a direction (which also steers) closes it
+    if game.show_help {
+        if !matches!(action, Some(Action::Help | Action::Move(_))) {
+            return InputOutcome::Continue;
+        }
+        game.show_help = false;
+        game.toggle_pause();
+        if action == Some(Action::Help) {
+            return InputOutcome::Continue;
         }
-        Some(Input::Character('p')) | Some(Input::Character('P')) => {
-            // Don't allow pause during initial waiting state
-            if !game.waiting_for_start {
+    }
+
+    match action {
+        // Mid-game a stray Q shouldn't throw the run away: ask first
+        Some(Action::Quit) if game.status == GameStatus::Playing && !game.terminal_too_small => {
+            game.toggle_pause();
+            game.confirm_quit = true;
+        }
+        Some(Action::Quit) => {
+            return InputOutcome::Quit; // Quit game
+        }
+        // Everything else waits until the field fits again
+        _ if game.terminal_too_small => {}
+        // Don't allow pause during initial waiting state (or ever in a hardcore run)
+        Some(Action::Pause) if !game.waiting_for_start && game.settings.allow_pause => {
+            if game.status == GameStatus::Playing {
+                game.toggle_pause();
+                match renderer.show_pause_menu(game) {
+                    PauseChoice::Resume => game.toggle_pause(),
+                    PauseChoice::Restart => {
+                        game.restart();
+                        return InputOutcome::Restart;
+                    }
+                    PauseChoice::Quit => return InputOutcome::Quit,
+                }
+            } else {
+                // Already paused (e.g. by a resize) - P simply resumes
                 game.toggle_pause();
             }
         }
-        Some(Input::Character('r')) | Some(Input::Character('R')) => {
-            if game.status == GameStatus::GameOver {
-                // Restart game with same dimensions, offsets, and difficulty
-                let delay_ms = game.frame_delay.as_millis() as u64;
-                *game = GameState::new(game.game_width, game.game_height, game.offset_x, game.offset_y, delay_ms);
-            }
+        Some(Action::Restart) if game.is_finished() => {
+            // Restart game with same settings and offsets (speed resets to start)
+            game.restart();
+            return InputOutcome::Restart;
         }
-        Some(Input::KeyUp) => {
-            game.set_direction(Direction::Up);
-            // Start the game when first arrow key is pressed
-            game.waiting_for_start = false;
+        // The help holds the game paused, so hardcore runs go without it
+        Some(Action::Help) if game.status == GameStatus::Playing && game.settings.allow_pause => {
+            game.toggle_pause();
+            game.show_help = true;
         }
-        Some(Input::KeyDown) => {
-            game.set_direction(Direction::Down);
-            // Start the game when first arrow key is pressed
-            game.waiting_for_start = false;
+        Some(Action::SlowMo) if renderer.manual_step => return InputOutcome::Step,
+        Some(Action::SlowMo) if game.will_move() => game.toggle_slowmo(),
+        Some(Action::Faster) if !game.is_finished() => game.adjust_speed(true),
+        Some(Action::Slower) if !game.is_finished() => game.adjust_speed(false),
+        Some(Action::Menu) if game.is_finished() => return InputOutcome::Menu,
+        Some(Action::Seed) if game.is_finished() => game.fixed_seed = !game.fixed_seed,
+        Some(Action::Reroll) if game.will_move() => return InputOutcome::Reroll,
+        // Practice mode: back to a few moves before the crash, paused (no-op without a history)
+        Some(Action::Rewind) if game.status == GameStatus::GameOver => {
+            game.rewind(REWIND_MOVES);
         }
-        Some(Input::KeyLeft) => {
-            game.set_direction(Direction::Left);
-            // Start the game when first arrow key is pressed
-            game.waiting_for_start = false;
+        Some(Action::Scores) if game.is_finished() => {
+            renderer.show_leaderboard(game, &scores::load(&game.settings.mode_name()), None);
         }
-        Some(Input::KeyRight) => {
-            game.set_direction(Direction::Right);
-            // Start the game when first arrow key is pressed
-            game.waiting_for_start = false;
+        Some(Action::Move(direction)) => {
+            // In two-player mode the arrows (and numpad) steer player 1 and the move bindings player 2
+            let bound_key = matches!(input, Input::Character(_)) && numpad.is_none();
+            let player = if game.settings.versus && bound_key { 1 } else { 0 };
+            // Chaos mode's powerup turns the keys around; steer() still refuses the reversed
+            // direction if it points back into the snake
+            let direction = if game.controls_inverted() { direction.opposite() } else { direction };
+            steer(game, player, direction);
         }
         _ => {}
     }
 
-    true // Continue game
+    InputOutcome::Continue
+}
+
+/// Turn a player's snake, from the keyboard or the input pipe
+fn steer(game: &mut GameState, player: usize, direction: Direction) {
+    // A direction also ends an auto-pause
+    if game.auto_paused {
+        game.toggle_pause();
+    }
+    game.set_player_direction(player, direction);
+    // The first direction starts the countdown; later ones are buffered
+    game.start_countdown();
+}
+
+/// Input during a replay: only quitting is possible
+fn handle_replay_input(renderer: &Renderer) -> bool {
+    match renderer.window.getch() {
+        Some(Input::Character(c)) => renderer.keys.action_for(c) != Some(Action::Quit),
+        _ => true,
+    }
 }
 
 // ============================================================================
@@ -828,72 +2898,473 @@ fn handle_input(window: &Window, game: &mut GameState) -> bool {
 // ============================================================================
 
 fn main() {
-    // Initialize renderer (and curses)
-    let renderer = match Renderer::new() {
-        Ok(r) => r,
+    // Parse command-line options before curses takes over the terminal
+    let options = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
         Err(e) => {
-            eprintln!("Failed to initialize renderer: {}", e);
-            return;
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
         }
     };
 
-    // Show size selection menu
-    let size_index = match renderer.show_size_menu() {
-        Some(idx) => idx,
-        None => return, // User quit from menu
+    // Load the replay up front so a bad file is reported before curses starts
+    let replay = match options.replay.as_deref().map(Recording::load).transpose() {
+        Ok(replay) => replay,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
     };
 
-    let selected_size = &FIELD_SIZES[size_index];
-
-    // Show difficulty selection menu
-    let difficulty_index = match renderer.show_difficulty_menu() {
-        Some(idx) => idx,
-        None => return, // User quit from menu
+    // Same for a map, which has to be a size the custom field entry would accept too
+    let map = match options.map.as_deref().map(Map::load).transpose() {
+        Ok(Some(map)) if !CUSTOM_WIDTH_RANGE.contains(&map.width) || !CUSTOM_HEIGHT_RANGE.contains(&map.height) => {
+            eprintln!(
+                "{}: a {}x{} map is out of range (width {}-{}, height {}-{})",
+                options.map.unwrap_or_default().display(),
+                map.width,
+                map.height,
+                CUSTOM_WIDTH_RANGE.start(),
+                CUSTOM_WIDTH_RANGE.end(),
+                CUSTOM_HEIGHT_RANGE.start(),
+                CUSTOM_HEIGHT_RANGE.end()
+            );
+            std::process::exit(1);
+        }
+        Ok(map) => map,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
     };
+    // The map sets the field size, like --size and --field
+    let fixed_size = map.as_ref().map(|map| (map.width, map.height)).or(options.size);
+
+    // Headless runs never touch the terminal: play the whole game and report
+    if options.headless {
+        let settings = match &replay {
+            Some(recording) => recording.settings,
+            None => {
+                let (width, height) = fixed_size.unwrap_or((FIELD_SIZES[2].width, FIELD_SIZES[2].height));
+                GameSettings {
+                    min_frame_ms: options.min_frame.unwrap_or(game::DEFAULT_MIN_FRAME_MS),
+                    max_frame_ms: options.max_frame.unwrap_or(game::DEFAULT_MAX_FRAME_MS),
+                    start_length: options.start_length.unwrap_or(3),
+                    max_length: options.max_length.unwrap_or(0),
+                    fair_spawn: options.fair_spawn,
+                    combos: options.combos,
+                    chaos: options.chaos,
+                    valued_food: options.valued_food,
+                    time_limit: options.time_attack.map(Duration::from_secs),
+                    near_miss: options.near_miss,
+                    ..GameSettings::new(width, height, options.speed.unwrap_or(DIFFICULTIES[1].delay_ms))
+                }
+            }
+        };
+        let seed = replay.as_ref().map(|recording| recording.seed).or(options.seed);
+        let game = match seed {
+            Some(seed) => GameState::new_seeded(settings, 0, 0, seed),
+            None => GameState::new(settings, 0, 0),
+        };
+        let game = match game {
+            Ok(mut game) => {
+                if let Some(map) = &map {
+                    game.use_map(map.clone());
+                }
+                headless::run(game, replay.as_ref())
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        };
+        if options.json {
+            println!("{}", summary::to_json(&game));
+        } else {
+            println!("Score: {}  Frames: {}  Seed: {}", game.score, game.frame, game.seed);
+        }
+        return;
+    }
 
-    // Get delay value (either preset or custom)
-    let delay_ms = if difficulty_index == 3 {
-        // Custom difficulty - get user input
-        match renderer.get_custom_delay() {
-            Some(delay) => delay,
-            None => return, // User canceled custom input
+    // A missing pipe is no reason not to play; it just means keyboard-only
+    let input_pipe = options.input_pipe.clone().and_then(|path| {
+        if path.exists() {
+            Some(pipe::InputPipe::open(path))
+        } else {
+            eprintln!("Warning: input pipe {} not found, using the keyboard only", path.display());
+            None
         }
-    } else {
-        // Preset difficulty
-        DIFFICULTIES[difficulty_index].delay_ms
-    };
+    });
+
+    // Load key bindings; warnings must be printed before curses takes over the screen
+    let (config, warnings) = config::load();
+    for warning in &warnings {
+        eprintln!("Warning: {}", warning);
+    }
 
-    // Calculate offsets to center the game window
-    let (offset_x, offset_y) = renderer.calculate_offsets(selected_size.width, selected_size.height);
+    // Ctrl+C (or a kill) would otherwise leave the terminal without echo and line buffering; the
+    // handler runs on a thread of its own where curses isn't safe to use, so it only sets a flag
+    if let Err(e) = ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst)) {
+        eprintln!("Warning: Ctrl+C won't restore the terminal: {}", e);
+    }
 
-    // Initialize game state with selected size and difficulty
-    let mut game = GameState::new(
-        selected_size.width,
-        selected_size.height,
-        offset_x,
-        offset_y,
-        delay_ms,
-    );
+    // Initialize renderer (and curses)
+    let mut renderer = match Renderer::new(
+        config.keys,
+        options.theme.unwrap_or(&THEMES[0]),
+        options.palette.unwrap_or(&PALETTES[0]),
+        options.rainbow,
+        options.grid,
+        options.wide,
+        options.ascii,
+    ) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Failed to initialize renderer: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if options.debug {
+        renderer.frame_timer = Some(FrameTimer::new());
+    }
+    renderer.title = options.title;
+    renderer.fog = options.fog;
+    renderer.scroll = options.scroll;
+    renderer.manual_step = options.step;
+    renderer.vision = options.vision;
+    renderer.afterimage = options.afterimage;
+    renderer.fade = options.fade;
+
+    // Only the first game is recorded (the recording starts along with it)
+    let mut recording = None;
+    let mut recording_done = false;
+
+    // --json: one summary line per game, printed once curses has let go of the screen
+    let mut summaries = Vec::new();
+
+    // Game-over beeps still to ring, one per frame so the loop never blocks
+    let mut pending_beeps = 0;
+
+    // Each pass sets up one game and plays it (restarts included) until the player quits,
+    // or goes back to the menus for a different one
+    let mut previous_size = None;
+    'session: loop {
+        // Replays bring their own settings, and --size skips the menus (--map every time);
+        // otherwise ask (a later trip back to the menus starts from the size picked last time)
+        let mut settings = match (&replay, fixed_size, previous_size) {
+            (Some(recording), _, _) => recording.settings,
+            (None, Some((width, height)), previous) if previous.is_none() || map.is_some() => {
+                let delay_ms = options.speed.unwrap_or(DIFFICULTIES[1].delay_ms);
+                GameSettings::new(width, height, delay_ms)
+            }
+            _ => match choose_settings(&renderer, options.speed, previous_size) {
+                Some(settings) => settings,
+                None => break 'session, // User quit from a menu
+            },
+        };
+        if let Some(min_frame) = options.min_frame {
+            settings.min_frame_ms = min_frame;
+        }
+        if let Some(max_frame) = options.max_frame {
+            settings.max_frame_ms = max_frame;
+        }
+        if let Some(start_length) = options.start_length {
+            settings.start_length = start_length;
+        }
+        if let Some(max_length) = options.max_length {
+            settings.max_length = max_length;
+        }
+        settings.fair_spawn |= options.fair_spawn;
+        settings.combos |= options.combos;
+        settings.chaos |= options.chaos;
+        settings.valued_food |= options.valued_food;
+        settings.near_miss |= options.near_miss;
+        if let Some(seconds) = options.time_attack {
+            settings.time_limit = Some(Duration::from_secs(seconds));
+        }
+        previous_size = Some((settings.width, settings.height));
+
+        // The menus only offer sizes that fit, but replays, maps, --size, and --field aren't checked yet
+        if !renderer.field_fits(settings.width, settings.height) {
+            let (needed_width, needed_height) = renderer.required_size(settings.width, settings.height);
+            let (have_width, have_height) = (renderer.window.get_max_x(), renderer.window.get_max_y());
+            drop(renderer); // Restore the terminal before reporting
+            eprintln!(
+                "Terminal too small for a {}x{} field: it needs {}x{} characters, this one has {}x{}",
+                settings.width, settings.height, needed_width, needed_height, have_width, have_height
+            );
+            std::process::exit(1);
+        }
 
-    // Initial render
-    renderer.render(&game);
+        // Calculate offsets to center the game window
+        let (offset_x, offset_y) = renderer.calculate_offsets(settings.width, settings.height);
 
-    // Main game loop
-    loop {
-        // Handle input
-        if !handle_input(&renderer.window, &mut game) {
-            break; // User quit
+        // Initialize game state with selected size, modes, and difficulty
+        let seed = replay.as_ref().map(|recording| recording.seed).or(options.seed);
+        let game = match seed {
+            Some(seed) => GameState::new_seeded(settings, offset_x, offset_y, seed),
+            None => GameState::new(settings, offset_x, offset_y),
+        };
+        let mut game = match game {
+            Ok(game) => game,
+            Err(e) => {
+                drop(renderer); // Restore the terminal before reporting
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        };
+        if let Some(map) = &map {
+            game.use_map(map.clone());
+        }
+        game.high_score = highscore::load(&settings.mode_name());
+        game.death_frames = game::frames_for(DEATH_ANIMATION, settings.delay_ms);
+        if options.practice {
+            game.rewind_limit = REWIND_HISTORY;
+        }
+        game.fixed_seed = options.fixed_seed;
+        if let Some(recording) = &replay {
+            game.waiting_for_start = false; // Replays start moving right away
+            // A time attack ends at the recorded move, whatever the real clock says (never, if
+            // the recorded game ended some other way)
+            game.time_up_frame = Some(recording.time_up.unwrap_or(u64::MAX));
+        } else if options.no_wait {
+            game.skip_start_wait();
+        }
+        if replay.is_none() && !options.autopilot {
+            // Only a human can step away from the keyboard
+            let idle = Duration::from_secs(options.auto_pause.unwrap_or(DEFAULT_AUTO_PAUSE_SECS));
+            game.auto_pause_frames = game::frames_for(idle, settings.delay_ms);
         }
 
-        // Update game logic
-        game.update();
+        // Only the first game is recorded; restarts and new games from the menus end the recording
+        if recording.is_none() {
+            recording = options.record.as_ref().map(|_| Recording::new(game.seed, settings));
+        }
 
-        // Render current state
+        // Initial render
         renderer.render(&game);
 
-        // Frame rate control (uses difficulty-based delay)
-        thread::sleep(game.frame_delay);
+        // Main game loop: fixed timestep, so the snake moves at the same pace however long
+        // rendering takes; late steps are caught up (a few at most) before the next render
+        let mut next_step = Instant::now();
+        loop {
+            // Ctrl+C: leave straight away, shutting curses down on the way out like any quit
+            if interrupted() {
+                break 'session;
+            }
+
+            let mut steps = 0;
+            while Instant::now() >= next_step && steps < MAX_CATCH_UP_STEPS {
+                let input_started = Instant::now();
+
+                // Handle input (a replay only listens for quit)
+                let outcome = if let Some(recording) = &replay {
+                    for (player, direction) in recording.directions_at(game.frame) {
+                        game.set_player_direction(player, direction);
+                    }
+                    for _ in 0..recording.rerolls_at(game.frame) {
+                        game.reroll_food();
+                    }
+                    if handle_replay_input(&renderer) { InputOutcome::Continue } else { InputOutcome::Quit }
+                } else {
+                    handle_input(&renderer, &mut game)
+                };
+                match outcome {
+                    InputOutcome::Continue | InputOutcome::Step => {}
+                    // Rerolls draw on the game's RNG, so a recording has to replay them too
+                    InputOutcome::Reroll => {
+                        if game.reroll_food() && !recording_done {
+                            if let Some(recording) = &mut recording {
+                                recording.rerolls.push(game.frame);
+                            }
+                        }
+                    }
+                    InputOutcome::Quit => {
+                        // Quitting mid-game still reports the game in progress
+                        if options.json && !game.is_finished() {
+                            summaries.push(summary::to_json(&game));
+                        }
+                        break 'session;
+                    }
+                    // Also with --fixed-seed, where the restarted game can't be told apart by its seed
+                    InputOutcome::Restart => recording_done = true,
+                    InputOutcome::Menu => {
+                        recording_done = true;
+                        continue 'session;
+                    }
+                }
+                // An external program may steer player 1 too, one direction per step
+                if let Some(direction) = input_pipe.as_ref().and_then(|pipe| pipe.next_direction()) {
+                    game.frames_since_input = 0;
+                    steer(&mut game, 0, direction);
+                }
+                // A blocking menu (pause) stalls the loop; that time shouldn't be caught up on
+                let stalled = input_started.elapsed() > game.frame_duration;
+
+                // Autopilot steers player 1 (keys still pause, restart, and quit)
+                if options.autopilot {
+                    game.start_countdown();
+                    if game.will_move() {
+                        if let Some(direction) = autopilot::choose_direction(&game) {
+                            game.set_player_direction(0, direction);
+                        }
+                    }
+                }
+
+                // Turn-based (--step): the snakes only move on a step key press; the countdown
+                // and the death animation still run by themselves
+                let advance = !options.step || outcome == InputOutcome::Step || !game.will_move();
+
+                // Record the direction each snake is about to turn to on this move
+                if let Some(recording) = &mut recording {
+                    if game.is_finished() {
+                        recording_done = true; // Over - the recorded game is complete
+                    }
+                    if !recording_done && advance && game.will_move() {
+                        for (player, snake) in game.snakes.iter().enumerate() {
+                            if snake.alive && snake.next_direction != snake.direction {
+                                recording.moves.push((game.frame, player, snake.next_direction));
+                            }
+                        }
+                    }
+                }
+
+                // Update game logic
+                let was_finished = game.is_finished();
+                if advance {
+                    game.update();
+                }
+
+                // Audible feedback: one beep per food, two quick ones when the game ends
+                if options.sound {
+                    if game.ate_food {
+                        pancurses::beep();
+                    }
+                    if game.status == GameStatus::GameOver && !was_finished {
+                        pending_beeps = 2;
+                    }
+                    if pending_beeps > 0 {
+                        pancurses::beep();
+                        pending_beeps -= 1;
+                    }
+                }
+
+                if options.json && game.is_finished() && !was_finished {
+                    summaries.push(summary::to_json(&game));
+                }
+                // The clock runs on real time, so a replay needs to be told when it ran out
+                if game.time_up() && !was_finished && !recording_done {
+                    if let Some(recording) = &mut recording {
+                        recording.time_up = Some(game.frame);
+                    }
+                }
+                if game.is_finished() {
+                    renderer.session_best = renderer.session_best.max(game.score);
+                }
+
+                // Record a beaten high score once, as soon as the game ends
+                // (replays, versus games, autopilot runs, and practice games don't count)
+                let counts_for_high_score =
+                    replay.is_none() && !options.autopilot && !options.practice && !game.settings.versus;
+                if counts_for_high_score && game.is_finished() && game.score > game.high_score {
+                    game.high_score = game.score;
+                    game.new_high_score = true;
+                    // A failed write only loses the record; not worth interrupting the game over
+                    let _ = highscore::save(&game.settings.mode_name(), game.score);
+                }
+
+                // Good enough for the leaderboard: ask for a name and show where it landed
+                if counts_for_high_score && game.is_finished() && !was_finished {
+                    let mode = game.settings.mode_name();
+                    let mut leaderboard = scores::load(&mode);
+                    if leaderboard.qualifies(game.score) {
+                        if let Some(name) = renderer.prompt_name(&game) {
+                            let position = leaderboard.insert(&name, game.score);
+                            let _ = scores::save(&mode, &leaderboard);
+                            renderer.show_leaderboard(&game, &leaderboard, position);
+                        }
+                    }
+                }
+
+                next_step = if stalled {
+                    Instant::now() + game.step_duration()
+                } else {
+                    next_step + game.step_duration()
+                };
+                steps += 1;
+            }
+
+            // Too far behind to catch up: drop the missed steps instead of spiralling
+            if steps == MAX_CATCH_UP_STEPS && Instant::now() >= next_step {
+                next_step = Instant::now();
+            }
+
+            // Render current state
+            if let Some(frame_timer) = &mut renderer.frame_timer {
+                frame_timer.tick();
+            }
+            renderer.render(&game);
+            renderer.show_score_in_title(game.score);
+
+            // Sleep until the next step is due
+            thread::sleep(next_step.saturating_duration_since(Instant::now()));
+        }
     }
 
-    // Cleanup happens automatically via Renderer's Drop trait
+    // Restore the terminal before writing the recording so errors can be shown
+    drop(renderer);
+    if interrupted() {
+        std::process::exit(130); // Ctrl+C: nothing more is saved or printed
+    }
+    for line in &summaries {
+        println!("{}", line);
+    }
+    if let (Some(recording), Some(path)) = (&recording, &options.record) {
+        if let Err(e) = recording.save(path) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Walk the player through the size, players, wall (with the obstacles, hazards, portals,
+/// movement, arena, poison, and tail), and difficulty (with the growth per food, pausing, assist, speed-up,
+/// hunger, and rules) menus
+/// A delay given with --speed leaves just the options on the difficulty menu; `previous_size`
+/// is offered first
+/// Returns None if they quit from any of them
+fn choose_settings(renderer: &Renderer, speed: Option<u64>, previous_size: Option<(i32, i32)>) -> Option<GameSettings> {
+    // Show size selection menu
+    let selected_size = renderer.show_size_menu(previous_size)?;
+
+    // Show one/two player selection menu
+    let versus = renderer.show_players_menu()?;
+
+    // Show wall mode selection menu: the walls, the obstacles, the hazards, the portals, the
+    // movement, the arena, the poison, and the tail
+    let board = renderer.show_wall_mode_menu()?;
+
+    // Show difficulty menu: the delay (--speed, preset, or custom), the growth per food,
+    // whether pausing is allowed, the assist, the speed-up, the hunger, and the rules
+    let difficulty = renderer.show_difficulty_menu(speed)?;
+
+    Some(GameSettings {
+        wrap_walls: board.wrap_walls,
+        doorways: board.doorways,
+        layout: board.layout,
+        self_collision_forgiveness: if difficulty.forgiving { FORGIVING_COLLISIONS } else { 0 },
+        versus,
+        hazard_trail: board.hazard_trail,
+        portals: board.portals,
+        diagonal: board.diagonal,
+        shrinking: board.shrinking,
+        growth_per_food: difficulty.growth_per_food,
+        poison_chance: POISON_CHANCES[board.poison],
+        tron: board.tron,
+        hunger_limit: HUNGER_LIMITS[difficulty.hunger],
+        allow_pause: difficulty.allow_pause,
+        assist: difficulty.assist,
+        speed_curve: difficulty.speed_curve,
+        ..GameSettings::new(selected_size.width, selected_size.height, difficulty.delay_ms)
+    })
 }
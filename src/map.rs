@@ -0,0 +1,109 @@
+//! User-made boards read from plain-text map files
+//!
+//! Every line of the file is one row of the board, and every character one cell:
+//!
+//! ```text
+//! ##########
+//! #........#
+//! #..S..F..#
+//! #........#
+//! ##########
+//! ```
+//!
+//! `#` is an obstacle, `.` is open, `S` is where the snake's head starts (exactly
+//! one), and `F` puts food on that cell at the start. All rows must be the same width.
+
+use crate::game::Point;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Map {
+    pub width: i32,
+    pub height: i32,
+    pub obstacles: Vec<Point>,
+    pub start: Point, // The snake's head
+    pub food: Vec<Point>, // Food on the board at the start (none = placed at random)
+}
+
+impl Map {
+    pub fn load(path: &Path) -> Result<Map, String> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        Map::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn parse(contents: &str) -> Result<Map, String> {
+        // Blank lines at the end of the file aren't rows
+        let rows: Vec<&str> = contents.trim_end().lines().map(|line| line.trim_end_matches('\r')).collect();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        if width == 0 {
+            return Err("the map is empty".to_string());
+        }
+
+        let mut obstacles = Vec::new();
+        let mut start = None;
+        let mut food = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            let row_width = row.chars().count();
+            if row_width != width {
+                return Err(format!(
+                    "line {}: the map isn't rectangular ({} cells wide, the first line has {})",
+                    y + 1,
+                    row_width,
+                    width
+                ));
+            }
+
+            for (x, cell) in row.chars().enumerate() {
+                let point = Point {
+                    x: x as i32,
+                    y: y as i32,
+                };
+                match cell {
+                    '#' => obstacles.push(point),
+                    '.' => {}
+                    'S' if start.is_some() => {
+                        return Err(format!("line {}: more than one start position (S)", y + 1));
+                    }
+                    'S' => start = Some(point),
+                    'F' => food.push(point),
+                    _ => {
+                        return Err(format!("line {}: unexpected '{}' (expected #, ., S, or F)", y + 1, cell));
+                    }
+                }
+            }
+        }
+
+        Ok(Map {
+            width: width as i32,
+            height: rows.len() as i32,
+            obstacles,
+            start: start.ok_or("no start position (S)")?,
+            food,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cells_and_size() {
+        let map = Map::parse("#####\n#S.F#\n#####\n\n").unwrap();
+        assert_eq!((map.width, map.height), (5, 3));
+        assert_eq!(map.start, Point { x: 1, y: 1 });
+        assert_eq!(map.food, vec![Point { x: 3, y: 1 }]);
+        assert_eq!(map.obstacles.len(), 12);
+    }
+
+    #[test]
+    fn rejects_broken_maps() {
+        assert!(Map::parse("").is_err());
+        assert!(Map::parse("#####\n#S.#\n#####").is_err());
+        assert!(Map::parse("#####\n#...#\n#####").is_err());
+        assert!(Map::parse("#####\n#S.S#\n#####").is_err());
+        assert!(Map::parse("#####\n#S.x#\n#####").is_err());
+    }
+}
@@ -0,0 +1,77 @@
+//! External control through a named pipe (--input-pipe)
+//!
+//! Another program writes one byte per turn: `u`, `d`, `l`, or `r` (any case).
+//! Everything else, such as newlines, is ignored. A background thread does the
+//! blocking open and reads, so the game loop only ever polls a channel; once
+//! the pipe fails or the writer closes it, the game carries on keyboard-only.
+
+use rust_snake::Direction;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+pub struct InputPipe {
+    directions: Receiver<Direction>,
+}
+
+impl InputPipe {
+    /// Start reading the pipe in the background (opening it waits for a writer)
+    pub fn open(path: PathBuf) -> InputPipe {
+        let (sender, directions) = mpsc::channel();
+        thread::spawn(move || {
+            let Ok(file) = File::open(&path) else { return };
+            for byte in BufReader::new(file).bytes() {
+                let Ok(byte) = byte else { return };
+                if let Some(direction) = direction_for(byte) {
+                    if sender.send(direction).is_err() {
+                        return; // The game is over
+                    }
+                }
+            }
+        });
+        InputPipe { directions }
+    }
+
+    /// Next direction sent over the pipe, if one is waiting
+    pub fn next_direction(&self) -> Option<Direction> {
+        self.directions.try_recv().ok()
+    }
+}
+
+/// Direction a protocol byte stands for
+fn direction_for(byte: u8) -> Option<Direction> {
+    match byte.to_ascii_lowercase() {
+        b'u' => Some(Direction::Up),
+        b'd' => Some(Direction::Down),
+        b'l' => Some(Direction::Left),
+        b'r' => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_directions_and_skips_other_bytes() {
+        let path = std::env::temp_dir().join(format!("rust-snake-pipe-test-{}", std::process::id()));
+        std::fs::write(&path, "u\nD x l r\n").unwrap();
+
+        let pipe = InputPipe::open(path.clone());
+        let mut received = Vec::new();
+        for _ in 0..1000 {
+            match pipe.next_direction() {
+                Some(direction) => received.push(direction),
+                None if received.len() == 4 => break,
+                None => thread::sleep(std::time::Duration::from_millis(1)),
+            }
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(received, vec![Direction::Up, Direction::Down, Direction::Left, Direction::Right]);
+        assert_eq!(direction_for(b'x'), None);
+    }
+}
@@ -0,0 +1,388 @@
+//! Recording games to a file and replaying them deterministically
+//!
+//! A recording stores the RNG seed, the game settings, and every direction
+//! change keyed by the move (frame) number it was applied before and the
+//! player (1 or 2) who made it, plus the moves before which the food was rerolled and,
+//! in a time attack, the move at which the clock ran out:
+//!
+//! ```text
+//! seed 1234
+//! size 30 20
+//! delay 100
+//! minframe 40
+//! maxframe 250
+//! wrap false
+//! layout blocks
+//! forgiveness 0
+//! versus false
+//! trail false
+//! portals false
+//! diagonal false
+//! shrinking false
+//! growth 1
+//! poison 0
+//! tron false
+//! hunger 0
+//! length 3
+//! maxlength 0
+//! fairspawn false
+//! doorways false
+//! pause true
+//! speedcurve score
+//! combos false
+//! chaos false
+//! valuedfood false
+//! timelimit none
+//! nearmiss false
+//! assist false
+//! 0 1 up
+//! 7 1 left
+//! 9 reroll
+//! 412 timeup
+//! ```
+
+use crate::game::{Direction, GameSettings, Layout, SpeedCurve, DEFAULT_MAX_FRAME_MS, DEFAULT_MIN_FRAME_MS};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    pub seed: u64,
+    pub settings: GameSettings,
+    pub moves: Vec<(u64, usize, Direction)>, // Frame number, player index, and the direction taken
+    pub rerolls: Vec<u64>, // Frame numbers the food was rerolled at, once per reroll
+    pub time_up: Option<u64>, // Frame number the time attack clock ran out at (it runs on real time)
+}
+
+impl Recording {
+    pub fn new(seed: u64, settings: GameSettings) -> Self {
+        Recording {
+            seed,
+            settings,
+            moves: Vec::new(),
+            rerolls: Vec::new(),
+            time_up: None,
+        }
+    }
+
+    /// Direction changes recorded for the given frame as (player index, direction)
+    pub fn directions_at(&self, frame: u64) -> impl Iterator<Item = (usize, Direction)> + '_ {
+        self.moves
+            .iter()
+            .filter(move |(move_frame, _, _)| *move_frame == frame)
+            .map(|&(_, player, direction)| (player, direction))
+    }
+
+    /// Number of food rerolls recorded for the given frame
+    pub fn rerolls_at(&self, frame: u64) -> usize {
+        self.rerolls.iter().filter(|&&reroll_frame| reroll_frame == frame).count()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_text()).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+
+    pub fn load(path: &Path) -> Result<Recording, String> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        Recording::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn to_text(&self) -> String {
+        let settings = &self.settings;
+        let mut text = format!(
+            "seed {}\nsize {} {}\ndelay {}\nminframe {}\nmaxframe {}\nwrap {}\nlayout {}\nforgiveness {}\nversus {}\ntrail {}\nportals {}\ndiagonal {}\nshrinking {}\ngrowth {}\npoison {}\ntron {}\nhunger {}\nlength {}\nmaxlength {}\nfairspawn {}\ndoorways {}\npause {}\nspeedcurve {}\ncombos {}\nchaos {}\nvaluedfood {}\ntimelimit {}\nnearmiss {}\nassist {}\n",
+            self.seed,
+            settings.width,
+            settings.height,
+            settings.delay_ms,
+            settings.min_frame_ms,
+            settings.max_frame_ms,
+            settings.wrap_walls,
+            layout_name(settings.layout),
+            settings.self_collision_forgiveness,
+            settings.versus,
+            settings.hazard_trail,
+            settings.portals,
+            settings.diagonal,
+            settings.shrinking,
+            settings.growth_per_food,
+            settings.poison_chance,
+            settings.tron,
+            settings.hunger_limit,
+            settings.start_length,
+            settings.max_length,
+            settings.fair_spawn,
+            settings.doorways,
+            settings.allow_pause,
+            speed_curve_name(settings.speed_curve),
+            settings.combos,
+            settings.chaos,
+            settings.valued_food,
+            settings.time_limit.map_or("none".to_string(), |limit| limit.as_secs().to_string()),
+            settings.near_miss,
+            settings.assist
+        );
+        for (frame, player, direction) in &self.moves {
+            text.push_str(&format!("{} {} {}\n", frame, player + 1, direction_name(*direction)));
+        }
+        for frame in &self.rerolls {
+            text.push_str(&format!("{} reroll\n", frame));
+        }
+        if let Some(frame) = self.time_up {
+            text.push_str(&format!("{} timeup\n", frame));
+        }
+        text
+    }
+
+    fn parse(contents: &str) -> Result<Recording, String> {
+        let mut seed = None;
+        let mut size = None;
+        let mut delay_ms = None;
+        let mut min_frame_ms = DEFAULT_MIN_FRAME_MS;
+        let mut max_frame_ms = DEFAULT_MAX_FRAME_MS;
+        let mut wrap_walls = false;
+        let mut layout = Layout::Open;
+        let mut forgiveness = 0;
+        let mut versus = false;
+        let mut hazard_trail = false;
+        let mut portals = false;
+        let mut diagonal = false;
+        let mut shrinking = false;
+        let mut growth_per_food = 1;
+        let mut poison_chance = 0;
+        let mut tron = false;
+        let mut hunger_limit = 0;
+        let mut start_length = 3;
+        let mut max_length = 0;
+        let mut fair_spawn = false;
+        let mut doorways = false;
+        let mut allow_pause = true;
+        let mut speed_curve = SpeedCurve::Score;
+        let mut combos = false;
+        let mut chaos = false;
+        let mut valued_food = false;
+        let mut time_limit = None;
+        let mut near_miss = false;
+        let mut assist = false;
+        let mut moves = Vec::new();
+        let mut rerolls = Vec::new();
+        let mut time_up = None;
+
+        for (line_number, line) in contents.lines().enumerate() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let invalid = || format!("line {}: invalid entry '{}'", line_number + 1, line.trim());
+
+            match fields.as_slice() {
+                [] => {}
+                ["seed", value] => seed = Some(value.parse().map_err(|_| invalid())?),
+                ["size", width, height] => {
+                    size = Some((
+                        width.parse().map_err(|_| invalid())?,
+                        height.parse().map_err(|_| invalid())?,
+                    ))
+                }
+                ["delay", value] => delay_ms = Some(value.parse().map_err(|_| invalid())?),
+                ["minframe", value] => min_frame_ms = value.parse().map_err(|_| invalid())?,
+                ["maxframe", value] => max_frame_ms = value.parse().map_err(|_| invalid())?,
+                ["wrap", value] => wrap_walls = value.parse().map_err(|_| invalid())?,
+                ["layout", name] => layout = parse_layout(name).ok_or_else(invalid)?,
+                ["forgiveness", value] => forgiveness = value.parse().map_err(|_| invalid())?,
+                ["versus", value] => versus = value.parse().map_err(|_| invalid())?,
+                ["trail", value] => hazard_trail = value.parse().map_err(|_| invalid())?,
+                ["portals", value] => portals = value.parse().map_err(|_| invalid())?,
+                ["diagonal", value] => diagonal = value.parse().map_err(|_| invalid())?,
+                ["shrinking", value] => shrinking = value.parse().map_err(|_| invalid())?,
+                ["growth", value] => growth_per_food = value.parse().map_err(|_| invalid())?,
+                ["poison", value] => poison_chance = value.parse().map_err(|_| invalid())?,
+                ["tron", value] => tron = value.parse().map_err(|_| invalid())?,
+                ["hunger", value] => hunger_limit = value.parse().map_err(|_| invalid())?,
+                ["length", value] => start_length = value.parse().map_err(|_| invalid())?,
+                ["maxlength", value] => max_length = value.parse().map_err(|_| invalid())?,
+                ["fairspawn", value] => fair_spawn = value.parse().map_err(|_| invalid())?,
+                ["doorways", value] => doorways = value.parse().map_err(|_| invalid())?,
+                ["pause", value] => allow_pause = value.parse().map_err(|_| invalid())?,
+                ["speedcurve", name] => speed_curve = parse_speed_curve(name).ok_or_else(invalid)?,
+                ["combos", value] => combos = value.parse().map_err(|_| invalid())?,
+                ["chaos", value] => chaos = value.parse().map_err(|_| invalid())?,
+                ["valuedfood", value] => valued_food = value.parse().map_err(|_| invalid())?,
+                ["nearmiss", value] => near_miss = value.parse().map_err(|_| invalid())?,
+                ["assist", value] => assist = value.parse().map_err(|_| invalid())?,
+                ["timelimit", "none"] => time_limit = None,
+                ["timelimit", seconds] => {
+                    time_limit = Some(Duration::from_secs(seconds.parse().map_err(|_| invalid())?))
+                }
+                [frame, "reroll"] => rerolls.push(frame.parse().map_err(|_| invalid())?),
+                [frame, "timeup"] => time_up = Some(frame.parse().map_err(|_| invalid())?),
+                [frame, player, direction] => {
+                    let frame = frame.parse().map_err(|_| invalid())?;
+                    let player = match *player {
+                        "1" => 0,
+                        "2" => 1,
+                        _ => return Err(invalid()),
+                    };
+                    let direction = parse_direction(direction).ok_or_else(invalid)?;
+                    moves.push((frame, player, direction));
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        let (width, height) = size.ok_or("missing 'size' line")?;
+        Ok(Recording {
+            seed: seed.ok_or("missing 'seed' line")?,
+            settings: GameSettings {
+                min_frame_ms,
+                max_frame_ms,
+                wrap_walls,
+                layout,
+                self_collision_forgiveness: forgiveness,
+                versus,
+                hazard_trail,
+                portals,
+                diagonal,
+                shrinking,
+                growth_per_food,
+                poison_chance,
+                tron,
+                hunger_limit,
+                start_length,
+                max_length,
+                fair_spawn,
+                doorways,
+                allow_pause,
+                speed_curve,
+                combos,
+                chaos,
+                valued_food,
+                time_limit,
+                near_miss,
+                assist,
+                ..GameSettings::new(width, height, delay_ms.ok_or("missing 'delay' line")?)
+            },
+            moves,
+            rerolls,
+            time_up,
+        })
+    }
+}
+
+fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Up => "up",
+        Direction::Down => "down",
+        Direction::Left => "left",
+        Direction::Right => "right",
+        Direction::UpLeft => "up-left",
+        Direction::UpRight => "up-right",
+        Direction::DownLeft => "down-left",
+        Direction::DownRight => "down-right",
+    }
+}
+
+fn parse_direction(name: &str) -> Option<Direction> {
+    match name {
+        "up" => Some(Direction::Up),
+        "down" => Some(Direction::Down),
+        "left" => Some(Direction::Left),
+        "right" => Some(Direction::Right),
+        "up-left" => Some(Direction::UpLeft),
+        "up-right" => Some(Direction::UpRight),
+        "down-left" => Some(Direction::DownLeft),
+        "down-right" => Some(Direction::DownRight),
+        _ => None,
+    }
+}
+
+fn layout_name(layout: Layout) -> &'static str {
+    match layout {
+        Layout::Open => "open",
+        Layout::Blocks => "blocks",
+        Layout::Campaign => "campaign",
+    }
+}
+
+fn parse_layout(name: &str) -> Option<Layout> {
+    match name {
+        "open" => Some(Layout::Open),
+        "blocks" => Some(Layout::Blocks),
+        "campaign" => Some(Layout::Campaign),
+        _ => None,
+    }
+}
+
+fn speed_curve_name(speed_curve: SpeedCurve) -> &'static str {
+    match speed_curve {
+        SpeedCurve::Score => "score",
+        SpeedCurve::Length => "length",
+    }
+}
+
+fn parse_speed_curve(name: &str) -> Option<SpeedCurve> {
+    match name {
+        "score" => Some(SpeedCurve::Score),
+        "length" => Some(SpeedCurve::Length),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_round_trips_through_text() {
+        let settings = GameSettings {
+            min_frame_ms: 60,
+            max_frame_ms: 300,
+            wrap_walls: true,
+            layout: Layout::Blocks,
+            self_collision_forgiveness: 3,
+            versus: true,
+            hazard_trail: true,
+            portals: true,
+            diagonal: true,
+            shrinking: true,
+            growth_per_food: 3,
+            poison_chance: 25,
+            tron: true,
+            hunger_limit: 60,
+            start_length: 7,
+            max_length: 40,
+            fair_spawn: true,
+            doorways: true,
+            allow_pause: false,
+            speed_curve: SpeedCurve::Length,
+            combos: true,
+            chaos: true,
+            valued_food: true,
+            time_limit: Some(Duration::from_secs(90)),
+            near_miss: true,
+            assist: true,
+            ..GameSettings::new(30, 20, 70)
+        };
+        let mut recording = Recording::new(99, settings);
+        recording.moves.push((0, 0, Direction::Up));
+        recording.moves.push((12, 0, Direction::Left));
+        recording.moves.push((12, 1, Direction::Down));
+        recording.moves.push((20, 0, Direction::DownRight));
+        recording.rerolls.extend([15, 15]);
+        recording.time_up = Some(30);
+
+        let parsed = Recording::parse(&recording.to_text()).unwrap();
+
+        assert_eq!(parsed, recording);
+        let at_12: Vec<_> = parsed.directions_at(12).collect();
+        assert_eq!(at_12, vec![(0, Direction::Left), (1, Direction::Down)]);
+        assert_eq!(parsed.directions_at(5).count(), 0);
+        assert_eq!(parsed.rerolls_at(15), 2);
+    }
+
+    #[test]
+    fn rejects_incomplete_recordings() {
+        assert!(Recording::parse("seed 1\n0 1 up\n").is_err());
+        assert!(Recording::parse("seed 1\nsize 10 10\ndelay 100\n3 1 sideways\n").is_err());
+        assert!(Recording::parse("seed 1\nsize 10 10\ndelay 100\n3 3 up\n").is_err());
+    }
+}
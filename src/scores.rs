@@ -0,0 +1,177 @@
+//! Top-ten leaderboard for each game mode, stored as "mode score name" lines in the user's
+//! home directory
+//!
+//! Lines without a mode (from before scores were kept per mode) belong to the classic board.
+
+use crate::config::home_path;
+use rust_snake::game::CLASSIC_MODE;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const FILE_NAME: &str = ".rust-snake-scores";
+
+/// Entries kept on the leaderboard
+pub const MAX_ENTRIES: usize = 10;
+
+/// Longest name accepted for an entry
+pub const MAX_NAME_LEN: usize = 12;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub score: u32,
+}
+
+/// Best scores, highest first
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Leaderboard {
+    entries: Vec<Entry>,
+}
+
+impl Leaderboard {
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// True if the score would earn a place on the board
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_ENTRIES
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+    /// Add an entry in score order (after equal scores), dropping whoever falls off the end
+    /// Returns the entry's position, or None if it didn't make the board
+    pub fn insert(&mut self, name: &str, score: u32) -> Option<usize> {
+        if !self.qualifies(score) {
+            return None;
+        }
+
+        let position = self.entries.iter().take_while(|entry| entry.score >= score).count();
+        let name: String = name.trim().chars().take(MAX_NAME_LEN).collect();
+        self.entries.insert(position, Entry { name, score });
+        self.entries.truncate(MAX_ENTRIES);
+        Some(position)
+    }
+
+    fn to_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| format!("{} {}\n", entry.score, entry.name))
+            .collect()
+    }
+
+    /// Parse the file contents; None if any line is garbled
+    fn parse(contents: &str) -> Option<Leaderboard> {
+        let mut entries = Vec::new();
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            let (score, name) = line.split_once(' ')?;
+            entries.push(Entry {
+                name: name.to_string(),
+                score: score.parse().ok()?,
+            });
+        }
+
+        // Keep the invariants even if the file was edited by hand
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        entries.truncate(MAX_ENTRIES);
+        Some(Leaderboard { entries })
+    }
+}
+
+/// Split the file into each mode's leaderboard; None if any line is garbled
+fn parse_modes(contents: &str) -> Option<BTreeMap<String, Leaderboard>> {
+    let mut texts: BTreeMap<String, String> = BTreeMap::new();
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        let (mode, entry) = match line.split_once(' ') {
+            Some((score, _)) if score.parse::<u32>().is_ok() => (CLASSIC_MODE, line),
+            Some((mode, entry)) => (mode, entry),
+            None => return None,
+        };
+        let text = texts.entry(mode.to_string()).or_default();
+        text.push_str(entry);
+        text.push('\n');
+    }
+    texts
+        .into_iter()
+        .map(|(mode, text)| Some((mode, Leaderboard::parse(&text)?)))
+        .collect()
+}
+
+fn modes_to_text(boards: &BTreeMap<String, Leaderboard>) -> String {
+    let mut text = String::new();
+    for (mode, board) in boards {
+        for line in board.to_text().lines() {
+            text.push_str(&format!("{} {}\n", mode, line));
+        }
+    }
+    text
+}
+
+/// Location of the leaderboard file
+fn path() -> PathBuf {
+    home_path(FILE_NAME)
+}
+
+/// Every mode's leaderboard; a missing or corrupted file gives none
+fn load_modes() -> BTreeMap<String, Leaderboard> {
+    fs::read_to_string(path())
+        .ok()
+        .and_then(|contents| parse_modes(&contents))
+        .unwrap_or_default()
+}
+
+/// Read a mode's leaderboard; an empty board if there is none yet
+pub fn load(mode: &str) -> Leaderboard {
+    load_modes().remove(mode).unwrap_or_default()
+}
+
+/// Overwrite the stored leaderboard for a mode, keeping the other modes' boards
+pub fn save(mode: &str, leaderboard: &Leaderboard) -> io::Result<()> {
+    let mut boards = load_modes();
+    boards.insert(mode.to_string(), leaderboard.clone());
+    fs::write(path(), modes_to_text(&boards))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_best_ten_in_order() {
+        let mut board = Leaderboard::default();
+        for score in 1..=12 {
+            board.insert(&format!("p{}", score), score * 10);
+        }
+
+        let scores: Vec<u32> = board.entries().iter().map(|entry| entry.score).collect();
+        assert_eq!(scores, vec![120, 110, 100, 90, 80, 70, 60, 50, 40, 30]);
+        assert!(!board.qualifies(30));
+        assert!(board.qualifies(31));
+        assert_eq!(board.insert("late", 95), Some(3));
+        assert_eq!(board.insert("slow", 5), None);
+    }
+
+    #[test]
+    fn round_trips_and_rejects_corruption() {
+        let mut board = Leaderboard::default();
+        board.insert("Ada Lovelace", 250);
+        board.insert("bob", 40);
+
+        assert_eq!(Leaderboard::parse(&board.to_text()), Some(board));
+        assert_eq!(Leaderboard::parse("100 ok\nnot-a-score\n"), None);
+    }
+
+    #[test]
+    fn keeps_a_board_per_mode_and_reads_old_files() {
+        let boards = parse_modes("100 Ada Lovelace\nwrap 40 bob\n70 eve\n").unwrap();
+        let classic: Vec<&str> = boards[CLASSIC_MODE].entries().iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(classic, vec!["Ada Lovelace", "eve"]);
+        assert_eq!(boards["wrap"].entries()[0], Entry { name: "bob".to_string(), score: 40 });
+
+        assert_eq!(parse_modes(&modes_to_text(&boards)), Some(boards));
+        assert_eq!(parse_modes("wrap\n"), None);
+    }
+}
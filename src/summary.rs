@@ -0,0 +1,39 @@
+//! One-line JSON summary of a finished game for the --json flag
+//!
+//! ```text
+//! {"score":120,"length":15,"width":30,"height":20,"elapsed_secs":42.5,"won":false,"finished":true}
+//! ```
+//!
+//! All values are numbers or booleans, so the line is built by hand instead of
+//! pulling in a JSON library.
+
+use rust_snake::{GameState, GameStatus};
+
+/// Summary of the game as it stands; `finished` is false if the player quit mid-game
+pub fn to_json(game: &GameState) -> String {
+    format!(
+        "{{\"score\":{},\"length\":{},\"width\":{},\"height\":{},\"elapsed_secs\":{:.1},\"won\":{},\"finished\":{}}}",
+        game.score,
+        game.snake().body().len(),
+        game.game_width,
+        game.game_height,
+        game.elapsed().as_secs_f64(),
+        game.status == GameStatus::Won,
+        game.is_finished()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_snake::GameSettings;
+
+    #[test]
+    fn summarizes_a_fresh_game() {
+        let game = GameState::new(GameSettings::new(30, 20, 100), 0, 0).unwrap();
+        assert_eq!(
+            to_json(&game),
+            r#"{"score":0,"length":3,"width":30,"height":20,"elapsed_secs":0.0,"won":false,"finished":false}"#
+        );
+    }
+}